@@ -0,0 +1,341 @@
+use std::{
+    fs::File,
+    io::{Cursor, Error as IoError, Read, Seek},
+    path::Path,
+};
+
+use thiserror::Error as ThisError;
+use zip::{ZipArchive, result::ZipError};
+
+use crate::beatmap::{Beatmap, BeatmapError, checksum::md5_hex, decode::DecodedBeatmap};
+
+const FORMAT_HEADER: &str = "osu file format v";
+
+/// Largest decompressed size of a single .osu entry, in bytes
+pub const MAX_ENTRY_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Largest decompressed size of all .osu entries in an archive, in bytes
+pub const MAX_TOTAL_SIZE: u64 = 128 * 1024 * 1024;
+
+#[derive(Debug, ThisError)]
+pub enum ArchiveError {
+    #[error("Archive is corrupt or not a zip file")]
+    Corrupt(#[from] ZipError),
+    #[error("Failed to read archive")]
+    Io(#[from] IoError),
+    #[error("Archive has no entry named {0:?}")]
+    EntryNotFound(String),
+    #[error("Archive entry {0:?} is not a beatmap")]
+    NotABeatmap(String),
+    #[error("Archive entry {0:?} is larger than {MAX_ENTRY_SIZE} bytes")]
+    EntryTooLarge(String),
+    #[error("Archive's beatmaps are larger than {MAX_TOTAL_SIZE} bytes in total")]
+    TooLarge,
+    #[error("Beatmap {0:?} is not valid UTF-8")]
+    InvalidUtf8(String),
+    #[error("Beatmap error")]
+    Beatmap(#[from] BeatmapError),
+}
+
+/// A single difficulty inside a [`BeatmapArchive`]
+#[derive(Clone, Debug)]
+pub struct ArchiveEntry {
+    name: String,
+    version: String,
+    checksum: String,
+    content: Vec<u8>,
+}
+
+impl ArchiveEntry {
+    /// Path of the entry inside the archive
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Difficulty name, as in the `Version` metadata field
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// MD5 checksum of the entry, see [`Beatmap::checksum`]
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+
+    /// Raw contents of the .osu file
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+
+    /// Loads the entry with [`Beatmap::from_text`]
+    ///
+    /// # Errors
+    /// Returns an [`ArchiveError::InvalidUtf8`] if the file isn't valid UTF-8
+    /// Returns an [`ArchiveError::Beatmap`] if osu-native fails to load the beatmap
+    pub fn load(&self) -> Result<Beatmap, ArchiveError> {
+        let text = String::from_utf8(self.content.clone())
+            .map_err(|_| ArchiveError::InvalidUtf8(self.name.clone()))?;
+
+        Ok(Beatmap::from_text(text)?)
+    }
+}
+
+/// An .osz beatmap set, read fully into memory
+///
+/// Only .osu entries are kept; audio, backgrounds and storyboards are skipped.
+/// So are .osu entries without the file format header, e.g. empty leftovers,
+/// see [`BeatmapArchive::skipped`]
+///
+/// # Examples
+/// ```no_run
+/// # use osu_native::beatmap::archive::BeatmapArchive;
+/// let archive = BeatmapArchive::open("set.osz")?;
+/// for entry in archive.entries() {
+///     let beatmap = entry.load()?;
+///     println!("{} ({}): AR{}", entry.version(), entry.checksum(), beatmap.approach_rate);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct BeatmapArchive {
+    entries: Vec<ArchiveEntry>,
+    skipped: Vec<String>,
+}
+
+impl BeatmapArchive {
+    /// Reads an .osz archive from a path
+    ///
+    /// # Errors
+    /// See [`BeatmapArchive::from_reader`]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ArchiveError> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Reads an .osz archive already held in memory
+    ///
+    /// # Errors
+    /// See [`BeatmapArchive::from_reader`]
+    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Result<Self, ArchiveError> {
+        Self::from_reader(Cursor::new(bytes.as_ref()))
+    }
+
+    /// Reads an .osz archive
+    ///
+    /// Sizes are checked while decompressing since the ones in the zip headers
+    /// can't be trusted
+    ///
+    /// # Errors
+    /// Returns an [`ArchiveError::Corrupt`] if the archive can't be decompressed
+    /// Returns an [`ArchiveError::EntryTooLarge`] or [`ArchiveError::TooLarge`] if an
+    /// .osu entry exceeds [`MAX_ENTRY_SIZE`] or all of them exceed [`MAX_TOTAL_SIZE`]
+    pub fn from_reader(reader: impl Read + Seek) -> Result<Self, ArchiveError> {
+        let mut zip = ZipArchive::new(reader)?;
+        let mut entries = Vec::new();
+        let mut skipped = Vec::new();
+        let mut remaining = MAX_TOTAL_SIZE;
+
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i)?;
+
+            if !file.is_file() || !file.name().to_ascii_lowercase().ends_with(".osu") {
+                continue;
+            }
+
+            let name = file.name().to_owned();
+            let limit = MAX_ENTRY_SIZE.min(remaining);
+            let mut content = Vec::new();
+            // One byte past the limit tells an exact fit apart from an oversized entry
+            (&mut file).take(limit + 1).read_to_end(&mut content)?;
+
+            if content.len() as u64 > limit {
+                return Err(if limit < MAX_ENTRY_SIZE {
+                    ArchiveError::TooLarge
+                } else {
+                    ArchiveError::EntryTooLarge(name)
+                });
+            }
+
+            remaining -= content.len() as u64;
+
+            if !has_format_header(&content) {
+                skipped.push(name);
+
+                continue;
+            }
+
+            entries.push(ArchiveEntry {
+                version: DecodedBeatmap::from_bytes(&content).version,
+                checksum: md5_hex(&content),
+                name,
+                content,
+            });
+        }
+
+        Ok(Self { entries, skipped })
+    }
+
+    /// All difficulties in the archive, in archive order
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    /// Paths of the .osu entries that were skipped for lacking the file format header
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    /// Looks up a difficulty by its path inside the archive
+    ///
+    /// # Errors
+    /// Returns an [`ArchiveError::NotABeatmap`] if `name` isn't an .osu file or
+    /// was [skipped](Self::skipped)
+    /// Returns an [`ArchiveError::EntryNotFound`] if there is no such entry
+    pub fn entry(&self, name: &str) -> Result<&ArchiveEntry, ArchiveError> {
+        if !name.to_ascii_lowercase().ends_with(".osu")
+            || self.skipped.iter().any(|skipped| skipped == name)
+        {
+            return Err(ArchiveError::NotABeatmap(name.to_owned()));
+        }
+
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| ArchiveError::EntryNotFound(name.to_owned()))
+    }
+
+    /// Looks up a difficulty by its MD5 checksum
+    pub fn entry_by_checksum(&self, checksum: &str) -> Option<&ArchiveEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.checksum.eq_ignore_ascii_case(checksum))
+    }
+
+    /// Loads every difficulty in the archive
+    pub fn beatmaps(&self) -> impl Iterator<Item = Result<Beatmap, ArchiveError>> + '_ {
+        self.entries.iter().map(ArchiveEntry::load)
+    }
+}
+
+fn has_format_header(content: &[u8]) -> bool {
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+    let start = content
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(content.len());
+
+    content[start..].starts_with(FORMAT_HEADER.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{Cursor, Write},
+    };
+
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::{ArchiveError, BeatmapArchive, MAX_ENTRY_SIZE};
+    use crate::{beatmap::checksum::md5_hex, utils::initialize_path};
+
+    fn build_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        for (name, content) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content).unwrap();
+        }
+
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_archive_lists_difficulties() {
+        let map = fs::read(initialize_path()).unwrap();
+        let osz = build_archive(&[
+            ("John Grant - Toy Box (nik) [Expert].osu", &map),
+            ("audio.mp3", b"ID3"),
+            ("bg.jpg", b"\xFF\xD8"),
+        ]);
+
+        let archive = BeatmapArchive::from_bytes(osz).unwrap();
+        let entries = archive.entries();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].version(), "Expert");
+        assert_eq!(entries[0].checksum(), md5_hex(&map));
+        assert_eq!(entries[0].content(), map.as_slice());
+        assert!(archive.entry_by_checksum(&md5_hex(&map)).is_some());
+    }
+
+    #[test]
+    fn test_archive_loads_beatmap() {
+        let map = fs::read(initialize_path()).unwrap();
+        let osz = build_archive(&[("map.osu", &map)]);
+
+        let archive = BeatmapArchive::from_bytes(osz).unwrap();
+        let beatmap = archive.entry("map.osu").unwrap().load().unwrap();
+
+        assert_eq!(beatmap.approach_rate, 9.2);
+        assert_eq!(beatmap.checksum(), md5_hex(&map));
+    }
+
+    #[test]
+    fn test_archive_entry_errors() {
+        let map = fs::read(initialize_path()).unwrap();
+        let osz = build_archive(&[("map.osu", &map), ("audio.mp3", b"ID3")]);
+        let archive = BeatmapArchive::from_bytes(osz).unwrap();
+
+        assert!(matches!(
+            archive.entry("audio.mp3"),
+            Err(ArchiveError::NotABeatmap(_))
+        ));
+        assert!(matches!(
+            archive.entry("other.osu"),
+            Err(ArchiveError::EntryNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_archive_skips_fake_beatmaps() {
+        let osz = build_archive(&[
+            ("fake.osu", b"definitely not a beatmap"),
+            ("empty.osu", b""),
+            (
+                "real.osu",
+                b"osu file format v14\n\n[Metadata]\nVersion:Hard\n",
+            ),
+        ]);
+        let archive = BeatmapArchive::from_bytes(osz).unwrap();
+
+        assert_eq!(archive.entries().len(), 1);
+        assert_eq!(archive.entries()[0].version(), "Hard");
+        assert_eq!(archive.skipped(), ["fake.osu", "empty.osu"]);
+        assert!(matches!(
+            archive.entry("fake.osu"),
+            Err(ArchiveError::NotABeatmap(name)) if name == "fake.osu"
+        ));
+    }
+
+    #[test]
+    fn test_archive_rejects_corrupt_archive() {
+        assert!(matches!(
+            BeatmapArchive::from_bytes(b"PK\x03\x04 truncated"),
+            Err(ArchiveError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn test_archive_rejects_oversized_entry() {
+        let mut content = b"osu file format v14\n".to_vec();
+        content.resize(MAX_ENTRY_SIZE as usize + 1, b' ');
+        let osz = build_archive(&[("huge.osu", &content)]);
+
+        assert!(matches!(
+            BeatmapArchive::from_bytes(osz),
+            Err(ArchiveError::EntryTooLarge(name)) if name == "huge.osu"
+        ));
+    }
+}
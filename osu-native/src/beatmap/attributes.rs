@@ -0,0 +1,358 @@
+use crate::{
+    mods::{GameMods, number_setting},
+    ruleset::RulesetKind,
+};
+
+/// Preempt times at difficulty 0, 5 and 10
+const PREEMPT_RANGE: (f64, f64, f64) = (1800.0, 1200.0, 450.0);
+
+const OSU_GREAT_WINDOW_RANGE: (f64, f64, f64) = (80.0, 50.0, 20.0);
+const OSU_OK_WINDOW_RANGE: (f64, f64, f64) = (140.0, 100.0, 60.0);
+const OSU_MEH_WINDOW_RANGE: (f64, f64, f64) = (200.0, 150.0, 100.0);
+
+const TAIKO_GREAT_WINDOW_RANGE: (f64, f64, f64) = (50.0, 35.0, 20.0);
+const TAIKO_OK_WINDOW_RANGE: (f64, f64, f64) = (120.0, 80.0, 50.0);
+
+/// Mania's great window, which is what OD is displayed from
+const MANIA_GREAT_WINDOW_RANGE: (f64, f64, f64) = (64.0, 49.0, 34.0);
+
+const HARD_ROCK_RATIO: f64 = 1.4;
+const HARD_ROCK_CIRCLE_SIZE_RATIO: f64 = 1.3;
+const EASY_RATIO: f64 = 0.5;
+
+/// Beatmap difficulty settings as they end up in gameplay for a set of mods
+///
+/// Difficulty adjusting mods (HR/EZ/DA) are applied first, then AR and OD are
+/// converted to the values that give the same timings at the mods' clock rate.
+/// All times are in milliseconds of real time, i.e. already divided by the clock rate
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeatmapAttributes {
+    pub clock_rate: f64,
+    pub approach_rate: f64,
+    pub overall_difficulty: f64,
+    pub circle_size: f64,
+    pub drain_rate: f64,
+    /// Time between a hit object appearing and its start time.
+    /// Only meaningful for osu! and catch
+    pub approach_preempt: f64,
+    /// `None` for catch, which has no timing judgements
+    pub hit_windows: Option<HitWindows>,
+}
+
+/// Hit windows of each ruleset, in milliseconds either side of the object's start time
+#[derive(Clone, Debug, PartialEq)]
+pub enum HitWindows {
+    Osu {
+        great: f64,
+        ok: f64,
+        meh: f64,
+    },
+    Taiko {
+        great: f64,
+        ok: f64,
+    },
+    Mania {
+        perfect: f64,
+        great: f64,
+        good: f64,
+        ok: f64,
+        meh: f64,
+    },
+}
+
+/// Raw difficulty settings of a beatmap, before mods
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BaseDifficulty {
+    pub approach_rate: f64,
+    pub overall_difficulty: f64,
+    pub circle_size: f64,
+    pub drain_rate: f64,
+}
+
+impl BeatmapAttributes {
+    pub(crate) fn new(
+        base: BaseDifficulty,
+        ruleset: RulesetKind,
+        mods: &GameMods,
+        is_convert: bool,
+    ) -> Self {
+        let BaseDifficulty {
+            mut approach_rate,
+            mut overall_difficulty,
+            mut circle_size,
+            mut drain_rate,
+        } = base;
+
+        if let Some(adjust) = mods.get("DA") {
+            approach_rate = number_setting(adjust, "approach_rate").unwrap_or(approach_rate);
+            overall_difficulty =
+                number_setting(adjust, "overall_difficulty").unwrap_or(overall_difficulty);
+            circle_size = number_setting(adjust, "circle_size").unwrap_or(circle_size);
+            drain_rate = number_setting(adjust, "drain_rate").unwrap_or(drain_rate);
+        }
+
+        // Mania's HR and EZ scale hit windows directly instead of difficulty settings
+        let mut mania_window_multiplier = 1.0;
+
+        if mods.contains("HR") {
+            if ruleset == RulesetKind::Mania {
+                mania_window_multiplier /= HARD_ROCK_RATIO;
+            } else {
+                circle_size = (circle_size * HARD_ROCK_CIRCLE_SIZE_RATIO).min(10.0);
+                approach_rate = (approach_rate * HARD_ROCK_RATIO).min(10.0);
+                overall_difficulty = (overall_difficulty * HARD_ROCK_RATIO).min(10.0);
+                drain_rate = (drain_rate * HARD_ROCK_RATIO).min(10.0);
+            }
+        }
+
+        if mods.contains("EZ") {
+            if ruleset == RulesetKind::Mania {
+                mania_window_multiplier *= HARD_ROCK_RATIO;
+            } else {
+                circle_size *= EASY_RATIO;
+                approach_rate *= EASY_RATIO;
+                overall_difficulty *= EASY_RATIO;
+                drain_rate *= EASY_RATIO;
+            }
+        }
+
+        let clock_rate = mods.clock_rate();
+
+        let approach_preempt = difficulty_range(approach_rate, PREEMPT_RANGE) / clock_rate;
+
+        let hit_windows = match ruleset {
+            RulesetKind::Osu => Some(HitWindows::Osu {
+                great: stable_window(overall_difficulty, OSU_GREAT_WINDOW_RANGE) / clock_rate,
+                ok: stable_window(overall_difficulty, OSU_OK_WINDOW_RANGE) / clock_rate,
+                meh: stable_window(overall_difficulty, OSU_MEH_WINDOW_RANGE) / clock_rate,
+            }),
+            RulesetKind::Taiko => Some(HitWindows::Taiko {
+                great: stable_window(overall_difficulty, TAIKO_GREAT_WINDOW_RANGE) / clock_rate,
+                ok: stable_window(overall_difficulty, TAIKO_OK_WINDOW_RANGE) / clock_rate,
+            }),
+            RulesetKind::Mania => Some(mania_hit_windows(
+                overall_difficulty,
+                mania_window_multiplier,
+                is_convert,
+                clock_rate,
+            )),
+            RulesetKind::Catch => None,
+        };
+
+        let display_window_range = match ruleset {
+            RulesetKind::Taiko => TAIKO_GREAT_WINDOW_RANGE,
+            RulesetKind::Mania => MANIA_GREAT_WINDOW_RANGE,
+            _ => OSU_GREAT_WINDOW_RANGE,
+        };
+        let great_window = difficulty_range(overall_difficulty, display_window_range) / clock_rate;
+
+        Self {
+            clock_rate,
+            approach_rate: inverse_difficulty_range(approach_preempt, PREEMPT_RANGE),
+            overall_difficulty: inverse_difficulty_range(great_window, display_window_range),
+            circle_size,
+            drain_rate,
+            approach_preempt,
+            hit_windows,
+        }
+    }
+}
+
+/// Maps a difficulty setting onto a range given by its values at 0, 5 and 10
+fn difficulty_range(difficulty: f64, (min, mid, max): (f64, f64, f64)) -> f64 {
+    if difficulty > 5.0 {
+        mid + (max - mid) * (difficulty - 5.0) / 5.0
+    } else if difficulty < 5.0 {
+        mid + (mid - min) * (difficulty - 5.0) / 5.0
+    } else {
+        mid
+    }
+}
+
+/// Inverse of [`difficulty_range`]
+fn inverse_difficulty_range(value: f64, (min, mid, max): (f64, f64, f64)) -> f64 {
+    if (value - mid).signum() == (max - mid).signum() {
+        (value - mid) / (max - mid) * 5.0 + 5.0
+    } else {
+        (value - mid) / (mid - min) * 5.0 + 5.0
+    }
+}
+
+/// Hit windows are floored to whole milliseconds and judged exclusively, like stable
+fn stable_window(difficulty: f64, range: (f64, f64, f64)) -> f64 {
+    difficulty_range(difficulty, range).floor() - 0.5
+}
+
+fn mania_hit_windows(
+    overall_difficulty: f64,
+    multiplier: f64,
+    is_convert: bool,
+    clock_rate: f64,
+) -> HitWindows {
+    let window = |ms: f64| ((ms * multiplier).floor() + 0.5) / clock_rate;
+
+    if is_convert {
+        let hard = overall_difficulty.round() > 4.0;
+
+        return HitWindows::Mania {
+            perfect: window(16.0),
+            great: window(if hard { 34.0 } else { 47.0 }),
+            good: window(if hard { 67.0 } else { 77.0 }),
+            ok: window(97.0),
+            meh: window(121.0),
+        };
+    }
+
+    HitWindows::Mania {
+        perfect: window(16.0),
+        great: window(64.0 - 3.0 * overall_difficulty),
+        good: window(97.0 - 3.0 * overall_difficulty),
+        ok: window(127.0 - 3.0 * overall_difficulty),
+        meh: window(151.0 - 3.0 * overall_difficulty),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rosu_mods::{Acronym, GameModSimple, simple::SettingSimple};
+
+    use super::HitWindows;
+    use crate::{beatmap::Beatmap, ruleset::RulesetKind, utils::initialize_path};
+
+    fn assert_approx(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-2,
+            "Expected {expected}, but got {actual}"
+        );
+    }
+
+    fn mod_vec(acronyms: &[&str]) -> Vec<GameModSimple> {
+        acronyms
+            .iter()
+            .map(|acronym| GameModSimple {
+                acronym: Acronym::from_str(acronym).unwrap(),
+                settings: Default::default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_attributes_no_mods() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let attributes = beatmap.attributes(RulesetKind::Osu, vec![]).unwrap();
+
+        assert_approx(attributes.approach_rate, 9.2);
+        assert_approx(attributes.overall_difficulty, 8.3);
+        assert_approx(attributes.approach_preempt, 570.0);
+        assert_eq!(
+            attributes.hit_windows,
+            Some(HitWindows::Osu {
+                great: 29.5,
+                ok: 72.5,
+                // OD is stored as f32, so 8.3 lands just below a whole 117ms
+                meh: 115.5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_attributes_double_time() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let attributes = beatmap
+            .attributes(RulesetKind::Osu, mod_vec(&["DT"]))
+            .unwrap();
+
+        assert_approx(attributes.approach_rate, 10.47);
+        assert_approx(attributes.overall_difficulty, 9.98);
+        assert_approx(attributes.approach_preempt, 380.0);
+        assert_approx(attributes.circle_size, 4.0);
+    }
+
+    #[test]
+    fn test_attributes_hard_rock() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let attributes = beatmap
+            .attributes(RulesetKind::Osu, mod_vec(&["HR"]))
+            .unwrap();
+
+        assert_approx(attributes.approach_rate, 10.0);
+        assert_approx(attributes.overall_difficulty, 10.0);
+        assert_approx(attributes.circle_size, 5.2);
+        assert_approx(attributes.drain_rate, 7.0);
+    }
+
+    #[test]
+    fn test_attributes_easy_half_time() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let attributes = beatmap
+            .attributes(RulesetKind::Osu, mod_vec(&["EZ", "HT"]))
+            .unwrap();
+
+        // AR 4.6 at 0.75x has a preempt of 1664ms
+        assert_approx(attributes.approach_preempt, 1664.0);
+        assert_approx(attributes.approach_rate, 1.13);
+        assert_approx(attributes.circle_size, 2.0);
+    }
+
+    #[test]
+    fn test_attributes_difficulty_adjust() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let attributes = beatmap
+            .attributes(
+                RulesetKind::Osu,
+                vec![GameModSimple {
+                    acronym: Acronym::from_str("DA").unwrap(),
+                    settings: [("approach_rate".into(), SettingSimple::Number(5.0))]
+                        .into_iter()
+                        .collect(),
+                }],
+            )
+            .unwrap();
+
+        assert_approx(attributes.approach_rate, 5.0);
+        assert_approx(attributes.approach_preempt, 1200.0);
+        assert_approx(attributes.overall_difficulty, 8.3);
+    }
+
+    #[test]
+    fn test_attributes_taiko_hit_windows() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let attributes = beatmap.attributes(RulesetKind::Taiko, vec![]).unwrap();
+
+        assert_eq!(
+            attributes.hit_windows,
+            Some(HitWindows::Taiko {
+                great: 24.5,
+                ok: 59.5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_attributes_mania_convert_hit_windows() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let attributes = beatmap.attributes(RulesetKind::Mania, vec![]).unwrap();
+
+        assert_eq!(
+            attributes.hit_windows,
+            Some(HitWindows::Mania {
+                perfect: 16.5,
+                great: 34.5,
+                good: 67.5,
+                ok: 97.5,
+                meh: 121.5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_attributes_catch_has_no_hit_windows() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let attributes = beatmap.attributes(RulesetKind::Catch, vec![]).unwrap();
+
+        assert_eq!(attributes.hit_windows, None);
+        assert_approx(attributes.approach_rate, 9.2);
+    }
+}
@@ -0,0 +1,561 @@
+use std::fmt;
+
+use crate::{
+    beatmap::{Beatmap, BeatmapError},
+    ruleset::RulesetKind,
+};
+
+const PLAYFIELD_WIDTH: f32 = 512.0;
+const PLAYFIELD_CENTER: (f32, f32) = (256.0, 192.0);
+
+const HITSOUND_WHISTLE: i32 = 2;
+const HITSOUND_FINISH: i32 = 4;
+
+#[derive(Clone, Debug)]
+struct TimingPoint {
+    time: f64,
+    beat_length: f64,
+    uninherited: bool,
+}
+
+#[derive(Clone, Debug)]
+enum HitObjectKind {
+    Circle,
+    Slider {
+        control_points: Vec<(f32, f32)>,
+        slides: u32,
+        length: f64,
+    },
+    Spinner {
+        end_time: f64,
+    },
+    Hold {
+        end_time: f64,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct HitObject {
+    time: f64,
+    position: (f32, f32),
+    new_combo: bool,
+    hitsound: i32,
+    kind: HitObjectKind,
+}
+
+/// Generates .osu file contents, mostly useful for tests and benchmarks
+///
+/// Objects don't have to be added in chronological order. A 120 BPM timing
+/// point at 0ms is written if none was added
+///
+/// # Examples
+/// ```no_run
+/// # use osu_native::{beatmap::builder::BeatmapBuilder, ruleset::RulesetKind};
+/// // 100 circles at 200 BPM, one every 1/4 beat
+/// let mut builder = BeatmapBuilder::new(RulesetKind::Osu).timing_point(0.0, 200.0);
+/// for i in 0..100 {
+///     builder = builder.circle(f64::from(i) * 75.0, (256.0, 192.0));
+/// }
+/// let beatmap = builder.build()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct BeatmapBuilder {
+    ruleset: RulesetKind,
+    title: String,
+    artist: String,
+    creator: String,
+    version: String,
+    hp_drain_rate: f32,
+    circle_size: f32,
+    overall_difficulty: f32,
+    approach_rate: f32,
+    slider_multiplier: f64,
+    slider_tick_rate: f64,
+    timing_points: Vec<TimingPoint>,
+    hit_objects: Vec<HitObject>,
+}
+
+impl BeatmapBuilder {
+    /// Creates an empty beatmap for `ruleset` with every difficulty setting at 5
+    ///
+    /// For mania, the circle size is the key count and defaults to 4
+    pub fn new(ruleset: RulesetKind) -> Self {
+        Self {
+            ruleset,
+            title: "Title".to_owned(),
+            artist: "Artist".to_owned(),
+            creator: "osu-native".to_owned(),
+            version: "Normal".to_owned(),
+            hp_drain_rate: 5.0,
+            circle_size: if ruleset == RulesetKind::Mania {
+                4.0
+            } else {
+                5.0
+            },
+            overall_difficulty: 5.0,
+            approach_rate: 5.0,
+            slider_multiplier: 1.4,
+            slider_tick_rate: 1.0,
+            timing_points: Vec::new(),
+            hit_objects: Vec::new(),
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn artist(mut self, artist: impl Into<String>) -> Self {
+        self.artist = artist.into();
+        self
+    }
+
+    pub fn creator(mut self, creator: impl Into<String>) -> Self {
+        self.creator = creator.into();
+        self
+    }
+
+    /// Sets the difficulty name
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    pub fn hp_drain_rate(mut self, hp_drain_rate: f32) -> Self {
+        self.hp_drain_rate = hp_drain_rate;
+        self
+    }
+
+    pub fn circle_size(mut self, circle_size: f32) -> Self {
+        self.circle_size = circle_size;
+        self
+    }
+
+    /// Sets the key count of a mania beatmap, same as [`BeatmapBuilder::circle_size`]
+    pub fn keys(self, keys: u8) -> Self {
+        self.circle_size(keys.into())
+    }
+
+    pub fn overall_difficulty(mut self, overall_difficulty: f32) -> Self {
+        self.overall_difficulty = overall_difficulty;
+        self
+    }
+
+    pub fn approach_rate(mut self, approach_rate: f32) -> Self {
+        self.approach_rate = approach_rate;
+        self
+    }
+
+    pub fn slider_multiplier(mut self, slider_multiplier: f64) -> Self {
+        self.slider_multiplier = slider_multiplier;
+        self
+    }
+
+    pub fn slider_tick_rate(mut self, slider_tick_rate: f64) -> Self {
+        self.slider_tick_rate = slider_tick_rate;
+        self
+    }
+
+    /// Adds an uninherited timing point changing the BPM at `time`
+    pub fn timing_point(mut self, time: f64, bpm: f64) -> Self {
+        self.timing_points.push(TimingPoint {
+            time,
+            beat_length: 60000.0 / bpm,
+            uninherited: true,
+        });
+        self
+    }
+
+    /// Adds an inherited timing point changing the slider velocity multiplier at `time`
+    pub fn slider_velocity(mut self, time: f64, multiplier: f64) -> Self {
+        self.timing_points.push(TimingPoint {
+            time,
+            beat_length: -100.0 / multiplier,
+            uninherited: false,
+        });
+        self
+    }
+
+    /// Adds a hit circle, or a fruit in osu!catch
+    pub fn circle(self, time: f64, position: (f32, f32)) -> Self {
+        self.push(time, position, 0, HitObjectKind::Circle)
+    }
+
+    /// Adds a hit circle that starts a new combo
+    pub fn new_combo_circle(mut self, time: f64, position: (f32, f32)) -> Self {
+        self = self.circle(time, position);
+        if let Some(object) = self.hit_objects.last_mut() {
+            object.new_combo = true;
+        }
+        self
+    }
+
+    /// Adds a bezier slider, or a juice stream in osu!catch
+    ///
+    /// `control_points` are absolute positions following `position`, `length` is in osu!pixels
+    pub fn slider(
+        self,
+        time: f64,
+        position: (f32, f32),
+        control_points: &[(f32, f32)],
+        slides: u32,
+        length: f64,
+    ) -> Self {
+        let kind = HitObjectKind::Slider {
+            control_points: control_points.to_vec(),
+            slides: slides.max(1),
+            length,
+        };
+
+        self.push(time, position, 0, kind)
+    }
+
+    /// Adds a spinner, or a banana shower in osu!catch
+    pub fn spinner(self, time: f64, end_time: f64) -> Self {
+        self.push(
+            time,
+            PLAYFIELD_CENTER,
+            0,
+            HitObjectKind::Spinner { end_time },
+        )
+    }
+
+    /// Adds an osu!taiko hit, `rim` selects a kat instead of a don
+    pub fn hit(self, time: f64, rim: bool, strong: bool) -> Self {
+        let mut hitsound = if rim { HITSOUND_WHISTLE } else { 0 };
+
+        if strong {
+            hitsound |= HITSOUND_FINISH;
+        }
+
+        self.push(time, PLAYFIELD_CENTER, hitsound, HitObjectKind::Circle)
+    }
+
+    /// Adds an osu!taiko drum roll spanning `length` osu!pixels
+    pub fn drum_roll(self, time: f64, length: f64) -> Self {
+        let (x, y) = PLAYFIELD_CENTER;
+
+        self.slider(time, PLAYFIELD_CENTER, &[(x + length as f32, y)], 1, length)
+    }
+
+    /// Adds an osu!mania note in the 0-based `column`
+    pub fn note(self, time: f64, column: u8) -> Self {
+        let position = self.column_position(column);
+
+        self.push(time, position, 0, HitObjectKind::Circle)
+    }
+
+    /// Adds a note in every one of `columns` at the same time
+    pub fn chord(self, time: f64, columns: impl IntoIterator<Item = u8>) -> Self {
+        columns
+            .into_iter()
+            .fold(self, |builder, column| builder.note(time, column))
+    }
+
+    /// Adds an osu!mania hold note in the 0-based `column`
+    pub fn hold(self, time: f64, column: u8, end_time: f64) -> Self {
+        let position = self.column_position(column);
+
+        self.push(time, position, 0, HitObjectKind::Hold { end_time })
+    }
+
+    /// Writes the beatmap in the .osu file format
+    pub fn to_osu_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// Loads the generated beatmap through osu-native
+    ///
+    /// # Errors
+    /// See [`Beatmap::from_text`]
+    pub fn build(&self) -> Result<Beatmap, BeatmapError> {
+        Beatmap::from_text(self.to_osu_string())
+    }
+
+    fn push(mut self, time: f64, position: (f32, f32), hitsound: i32, kind: HitObjectKind) -> Self {
+        self.hit_objects.push(HitObject {
+            time,
+            position,
+            new_combo: false,
+            hitsound,
+            kind,
+        });
+        self
+    }
+
+    /// Center of a mania column, the same way lazer maps x positions to columns
+    fn column_position(&self, column: u8) -> (f32, f32) {
+        let keys = self.circle_size.round().max(1.0);
+        let x = ((f32::from(column) + 0.5) * PLAYFIELD_WIDTH / keys).floor();
+
+        (x, PLAYFIELD_CENTER.1)
+    }
+}
+
+impl fmt::Display for BeatmapBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "osu file format v14")?;
+        writeln!(f)?;
+        writeln!(f, "[General]")?;
+        writeln!(f, "AudioFilename: audio.mp3")?;
+        writeln!(f, "AudioLeadIn: 0")?;
+        writeln!(f, "PreviewTime: -1")?;
+        writeln!(f, "StackLeniency: 0.7")?;
+        writeln!(f, "Mode: {}", i32::from(self.ruleset))?;
+        writeln!(f)?;
+        writeln!(f, "[Metadata]")?;
+        writeln!(f, "Title:{}", self.title)?;
+        writeln!(f, "TitleUnicode:{}", self.title)?;
+        writeln!(f, "Artist:{}", self.artist)?;
+        writeln!(f, "ArtistUnicode:{}", self.artist)?;
+        writeln!(f, "Creator:{}", self.creator)?;
+        writeln!(f, "Version:{}", self.version)?;
+        writeln!(f)?;
+        writeln!(f, "[Difficulty]")?;
+        writeln!(f, "HPDrainRate:{}", self.hp_drain_rate)?;
+        writeln!(f, "CircleSize:{}", self.circle_size)?;
+        writeln!(f, "OverallDifficulty:{}", self.overall_difficulty)?;
+        writeln!(f, "ApproachRate:{}", self.approach_rate)?;
+        writeln!(f, "SliderMultiplier:{}", self.slider_multiplier)?;
+        writeln!(f, "SliderTickRate:{}", self.slider_tick_rate)?;
+        writeln!(f)?;
+        writeln!(f, "[Events]")?;
+        writeln!(f)?;
+        writeln!(f, "[TimingPoints]")?;
+
+        let mut timing_points: Vec<_> = self.timing_points.iter().collect();
+        timing_points.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        let default_point = TimingPoint {
+            time: 0.0,
+            beat_length: 500.0,
+            uninherited: true,
+        };
+
+        if !timing_points.iter().any(|point| point.uninherited) {
+            timing_points.insert(0, &default_point);
+        }
+
+        for point in timing_points {
+            writeln!(
+                f,
+                "{},{},4,1,0,100,{},0",
+                point.time,
+                point.beat_length,
+                i32::from(point.uninherited)
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "[HitObjects]")?;
+
+        let mut hit_objects: Vec<_> = self.hit_objects.iter().collect();
+        hit_objects.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        for (i, object) in hit_objects.into_iter().enumerate() {
+            write_hit_object(f, object, i == 0)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_hit_object(f: &mut fmt::Formatter<'_>, object: &HitObject, first: bool) -> fmt::Result {
+    let (x, y) = object.position;
+    let time = object.time;
+    let hitsound = object.hitsound;
+    let new_combo = if object.new_combo || first { 4 } else { 0 };
+
+    match &object.kind {
+        HitObjectKind::Circle => {
+            writeln!(f, "{x},{y},{time},{},{hitsound},0:0:0:0:", 1 | new_combo)
+        }
+        HitObjectKind::Slider {
+            control_points,
+            slides,
+            length,
+        } => {
+            write!(f, "{x},{y},{time},{},{hitsound},B", 2 | new_combo)?;
+
+            for (x, y) in control_points {
+                write!(f, "|{x}:{y}")?;
+            }
+
+            writeln!(f, ",{slides},{length}")
+        }
+        HitObjectKind::Spinner { end_time } => writeln!(
+            f,
+            "{x},{y},{time},{},{hitsound},{end_time},0:0:0:0:",
+            8 | new_combo
+        ),
+        HitObjectKind::Hold { end_time } => {
+            writeln!(f, "{x},{y},{time},128,{hitsound},{end_time}:0:0:0:0:")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BeatmapBuilder;
+    use crate::{
+        beatmap::{
+            decode::{DecodedBeatmap, HitObjectKind},
+            lint::lint,
+        },
+        difficulty::{
+            DifficultyCalculator, catch::CatchDifficultyCalculator,
+            mania::ManiaDifficultyCalculator, osu::OsuDifficultyCalculator,
+            taiko::TaikoDifficultyCalculator,
+        },
+        ruleset::{Ruleset, RulesetKind},
+    };
+
+    /// 100 circles at 200 BPM, one every 1/4 beat
+    fn osu_stream() -> BeatmapBuilder {
+        (0..100).fold(
+            BeatmapBuilder::new(RulesetKind::Osu).timing_point(1000.0, 200.0),
+            |builder, i| {
+                let x = if i % 2 == 0 { 200.0 } else { 300.0 };
+                builder.circle(1000.0 + f64::from(i) * 75.0, (x, 192.0))
+            },
+        )
+    }
+
+    /// 7K chord stream at 180 BPM, one chord every 1/4 beat
+    fn mania_chords() -> BeatmapBuilder {
+        (0..64u8).fold(
+            BeatmapBuilder::new(RulesetKind::Mania)
+                .keys(7)
+                .timing_point(0.0, 180.0),
+            |builder, i| {
+                let time = 500.0 + f64::from(i) * 1000.0 / 12.0;
+                builder.chord(time, [i % 7, (i + 3) % 7])
+            },
+        )
+    }
+
+    fn taiko_pattern() -> BeatmapBuilder {
+        (0..32)
+            .fold(
+                BeatmapBuilder::new(RulesetKind::Taiko).timing_point(0.0, 160.0),
+                |builder, i| builder.hit(f64::from(i) * 187.5, i % 3 == 0, i % 8 == 0),
+            )
+            .drum_roll(6000.0, 280.0)
+    }
+
+    fn catch_pattern() -> BeatmapBuilder {
+        BeatmapBuilder::new(RulesetKind::Catch)
+            .timing_point(0.0, 150.0)
+            .circle(0.0, (64.0, 192.0))
+            .circle(400.0, (448.0, 192.0))
+            .slider(800.0, (100.0, 192.0), &[(400.0, 192.0)], 2, 300.0)
+            .spinner(3000.0, 5000.0)
+    }
+
+    #[test]
+    fn test_builder_output_is_clean() {
+        for builder in [
+            osu_stream(),
+            mania_chords(),
+            taiko_pattern(),
+            catch_pattern(),
+        ] {
+            assert_eq!(lint(&builder.to_osu_string()), []);
+        }
+    }
+
+    #[test]
+    fn test_builder_decodes() {
+        let decoded = DecodedBeatmap::from_text(&osu_stream().to_osu_string());
+        assert_eq!(decoded.mode, RulesetKind::Osu);
+        assert_eq!(decoded.hit_objects.len(), 100);
+        assert_eq!(decoded.beat_length_at(0.0), 300.0);
+
+        let decoded = DecodedBeatmap::from_text(&catch_pattern().to_osu_string());
+        let slider = &decoded.hit_objects[2];
+        assert_eq!(slider.kind, HitObjectKind::Slider);
+        // 300px twice at 140px per beat of 400ms
+        assert!((slider.end_time - slider.start_time - 600.0 / 140.0 * 400.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_builder_mania_columns() {
+        let text = BeatmapBuilder::new(RulesetKind::Mania)
+            .keys(7)
+            .chord(0.0, 0..7)
+            .hold(500.0, 6, 1000.0)
+            .to_osu_string();
+
+        let columns: Vec<_> = text
+            .lines()
+            .skip_while(|line| *line != "[HitObjects]")
+            .skip(1)
+            .map(|line| {
+                let x: f32 = line.split(',').next().unwrap().parse().unwrap();
+                (x * 7.0 / 512.0).floor() as u8
+            })
+            .collect();
+
+        assert_eq!(columns, [0, 1, 2, 3, 4, 5, 6, 6]);
+    }
+
+    #[test]
+    fn test_build_osu_stream() {
+        let beatmap = osu_stream().version("Stream").build().unwrap();
+        assert_eq!(beatmap.version().unwrap(), "Stream");
+
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let attributes = OsuDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+
+        assert_eq!(attributes.max_combo, 100);
+        assert!(attributes.star_rating > 0.0);
+    }
+
+    #[test]
+    fn test_build_mania_chords() {
+        let beatmap = mania_chords().build().unwrap();
+        assert_eq!(beatmap.circle_size, 7.0);
+
+        let ruleset = Ruleset::new(RulesetKind::Mania).unwrap();
+        let attributes = ManiaDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+
+        assert_eq!(attributes.max_combo, 128);
+        assert!(attributes.star_rating > 0.0);
+    }
+
+    #[test]
+    fn test_build_taiko_pattern() {
+        let beatmap = taiko_pattern().build().unwrap();
+
+        let ruleset = Ruleset::new(RulesetKind::Taiko).unwrap();
+        let attributes = TaikoDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+
+        assert_eq!(attributes.max_combo, 32);
+        assert!(attributes.star_rating > 0.0);
+    }
+
+    #[test]
+    fn test_build_catch_pattern() {
+        let beatmap = catch_pattern().build().unwrap();
+
+        let ruleset = Ruleset::new(RulesetKind::Catch).unwrap();
+        let attributes = CatchDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+
+        assert!(attributes.max_combo > 2);
+        assert!(attributes.star_rating > 0.0);
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use md5::{Digest, Md5};
+
+/// Computes the lowercase hex MD5 checksum of .osu file contents
+///
+/// This is the checksum osu! uses to reference beatmaps in scores and replays
+pub fn md5_hex(bytes: &[u8]) -> String {
+    let digest = Md5::digest(bytes);
+
+    digest
+        .iter()
+        .fold(String::with_capacity(32), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// Lookup table from beatmap checksums to .osu files in a directory tree
+///
+/// # Examples
+/// ```no_run
+/// # use osu_native::beatmap::checksum::ChecksumIndex;
+/// let index = ChecksumIndex::from_dir("Songs")?;
+/// if let Some(path) = index.get("f52cf288046c2f07335a280096a5b4fb") {
+///     println!("Replay was played on {}", path.display());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ChecksumIndex {
+    paths: HashMap<String, PathBuf>,
+}
+
+impl ChecksumIndex {
+    /// Hashes every .osu file under `dir`, recursively
+    ///
+    /// # Errors
+    /// Returns an [`io::Error`] if `dir` or one of the files can't be read
+    pub fn from_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut paths = HashMap::new();
+
+        visit_osu_files(dir.as_ref(), &mut |path| {
+            let checksum = md5_hex(&fs::read(&path)?);
+            paths.insert(checksum, path);
+
+            Ok(true)
+        })?;
+
+        Ok(Self { paths })
+    }
+
+    /// Path of the .osu file with the given checksum, if there is one
+    ///
+    /// The checksum is compared case-insensitively
+    pub fn get(&self, checksum: &str) -> Option<&Path> {
+        self.paths
+            .get(&checksum.to_ascii_lowercase())
+            .map(PathBuf::as_path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.paths
+            .iter()
+            .map(|(checksum, path)| (checksum.as_str(), path.as_path()))
+    }
+}
+
+/// Finds the .osu file with the given checksum under `dir`, recursively
+///
+/// Stops at the first match. Use [`ChecksumIndex`] when looking up many checksums
+///
+/// # Errors
+/// Returns an [`io::Error`] if `dir` or one of the files can't be read
+pub fn find_by_checksum(dir: impl AsRef<Path>, checksum: &str) -> io::Result<Option<PathBuf>> {
+    let checksum = checksum.to_ascii_lowercase();
+    let mut found = None;
+
+    visit_osu_files(dir.as_ref(), &mut |path| {
+        if md5_hex(&fs::read(&path)?) == checksum {
+            found = Some(path);
+            return Ok(false);
+        }
+
+        Ok(true)
+    })?;
+
+    Ok(found)
+}
+
+/// Calls `visit` for every .osu file under `dir` until it returns `false`
+///
+/// Symlinked directories aren't followed so links back into `dir` can't loop
+pub(crate) fn visit_osu_files(
+    dir: &Path,
+    visit: &mut impl FnMut(PathBuf) -> io::Result<bool>,
+) -> io::Result<bool> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        let keep_going = if entry.file_type()?.is_dir() {
+            visit_osu_files(&path, visit)?
+        } else if path.extension().is_some_and(|ext| ext == "osu") {
+            visit(path)?
+        } else {
+            true
+        };
+
+        if !keep_going {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{ChecksumIndex, find_by_checksum, md5_hex};
+    use crate::{
+        beatmap::Beatmap,
+        utils::{initialize_path, unique_temp_dir},
+    };
+
+    const TOY_BOX_MD5: &str = "f52cf288046c2f07335a280096a5b4fb";
+
+    #[test]
+    fn test_md5_hex() {
+        assert_eq!(md5_hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(md5_hex(&fs::read(initialize_path()).unwrap()), TOY_BOX_MD5);
+    }
+
+    #[test]
+    fn test_beatmap_checksum_from_path() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        assert_eq!(beatmap.checksum(), TOY_BOX_MD5);
+    }
+
+    #[test]
+    fn test_beatmap_checksum_from_text() {
+        let file = fs::read_to_string(initialize_path()).unwrap();
+        let beatmap = Beatmap::from_text(file).unwrap();
+        assert_eq!(beatmap.checksum(), TOY_BOX_MD5);
+    }
+
+    #[test]
+    fn test_lookup_in_directory() {
+        let dir = unique_temp_dir("osu-native-checksum-lookup");
+        let nested = dir.join("123 Artist - Title");
+        fs::create_dir_all(&nested).unwrap();
+        fs::copy(initialize_path(), nested.join("map.osu")).unwrap();
+        fs::write(nested.join("other.osu"), "osu file format v14").unwrap();
+        fs::write(nested.join("audio.mp3"), "not a beatmap").unwrap();
+
+        let index = ChecksumIndex::from_dir(&dir).unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(
+            index.get(&TOY_BOX_MD5.to_uppercase()),
+            Some(nested.join("map.osu").as_path())
+        );
+
+        let found = find_by_checksum(&dir, TOY_BOX_MD5).unwrap();
+        assert_eq!(found, Some(nested.join("map.osu")));
+        assert_eq!(find_by_checksum(&dir, &md5_hex(b"missing")).unwrap(), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lookup_skips_symlink_loops() {
+        let dir = unique_temp_dir("osu-native-checksum-symlink-loop");
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy(initialize_path(), dir.join("map.osu")).unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+
+        let index = ChecksumIndex::from_dir(&dir).unwrap();
+        assert_eq!(index.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,435 @@
+//! Minimal Rust-side reader for the parts of an .osu file osu-native doesn't expose
+//!
+//! This is intentionally lenient: osu-native is the source of truth for whether a
+//! beatmap is valid, so malformed lines are skipped instead of reported
+
+use crate::ruleset::RulesetKind;
+
+/// Beat length of the implicit timing point used when a map has none (60 BPM)
+pub(crate) const DEFAULT_BEAT_LENGTH: f64 = 1000.0;
+
+const MIN_BEAT_LENGTH: f64 = 6.0;
+const MAX_BEAT_LENGTH: f64 = 60000.0;
+pub(crate) const BASE_SCORING_DISTANCE: f64 = 100.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Section {
+    General,
+    Editor,
+    Metadata,
+    Difficulty,
+    Events,
+    TimingPoints,
+    Colours,
+    HitObjects,
+    Unknown,
+}
+
+impl Section {
+    pub(crate) fn from_header(line: &str) -> Option<Self> {
+        let name = line.strip_prefix('[')?.strip_suffix(']')?;
+
+        let section = match name {
+            "General" => Self::General,
+            "Editor" => Self::Editor,
+            "Metadata" => Self::Metadata,
+            "Difficulty" => Self::Difficulty,
+            "Events" => Self::Events,
+            "TimingPoints" => Self::TimingPoints,
+            "Colours" => Self::Colours,
+            "HitObjects" => Self::HitObjects,
+            _ => Self::Unknown,
+        };
+
+        Some(section)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TimingPoint {
+    pub time: f64,
+    pub beat_length: f64,
+    pub uninherited: bool,
+    pub kiai: bool,
+}
+
+impl TimingPoint {
+    /// Beat length clamped the same way lazer clamps it
+    pub(crate) fn clamped_beat_length(&self) -> f64 {
+        self.beat_length.clamp(MIN_BEAT_LENGTH, MAX_BEAT_LENGTH)
+    }
+
+    /// Slider velocity multiplier of an inherited point
+    fn slider_velocity(&self) -> f64 {
+        if self.uninherited || self.beat_length >= 0.0 {
+            return 1.0;
+        }
+
+        (100.0 / -self.beat_length).clamp(0.1, 10.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HitObjectKind {
+    Circle,
+    Slider,
+    Spinner,
+    Hold,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct HitObject {
+    pub start_time: f64,
+    pub end_time: f64,
+    pub kind: HitObjectKind,
+    /// Only set for sliders
+    pub path: Option<SliderPath>,
+}
+
+/// The parts of a slider's path that determine its duration and ticks
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SliderPath {
+    /// Number of times the path is traversed, at least 1
+    pub slides: f64,
+    /// Length in osu!pixels
+    pub length: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct BreakPeriod {
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
+impl BreakPeriod {
+    pub(crate) fn duration(&self) -> f64 {
+        (self.end_time - self.start_time).max(0.0)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DecodedBeatmap {
+    pub mode: RulesetKind,
+    pub version: String,
+    pub slider_multiplier: f64,
+    pub slider_tick_rate: f64,
+    pub bookmarks: Vec<f64>,
+    pub breaks: Vec<BreakPeriod>,
+    pub timing_points: Vec<TimingPoint>,
+    pub hit_objects: Vec<HitObject>,
+}
+
+impl DecodedBeatmap {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from_text(&String::from_utf8_lossy(bytes))
+    }
+
+    pub(crate) fn from_text(text: &str) -> Self {
+        let mut beatmap = Self {
+            slider_multiplier: 1.4,
+            slider_tick_rate: 1.0,
+            ..Default::default()
+        };
+        let mut section = Section::Unknown;
+
+        for line in text.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            if let Some(next) = Section::from_header(line) {
+                section = next;
+                continue;
+            }
+
+            match section {
+                Section::General => {
+                    if let Some(("Mode", value)) = split_key_value(line) {
+                        beatmap.mode = value
+                            .parse::<i32>()
+                            .ok()
+                            .and_then(|id| id.try_into().ok())
+                            .unwrap_or_default();
+                    }
+                }
+                Section::Metadata => {
+                    if let Some(("Version", value)) = split_key_value(line) {
+                        beatmap.version = value.to_owned();
+                    }
+                }
+                Section::Editor => {
+                    if let Some(("Bookmarks", value)) = split_key_value(line) {
+                        beatmap.bookmarks = value
+                            .split(',')
+                            .filter_map(|time| time.trim().parse().ok())
+                            .collect();
+                    }
+                }
+                // Clamped to the same ranges as lazer
+                Section::Difficulty => match split_key_value(line) {
+                    Some(("SliderMultiplier", value)) => {
+                        if let Ok(multiplier) = value.parse::<f64>() {
+                            beatmap.slider_multiplier = multiplier.clamp(0.4, 3.6);
+                        }
+                    }
+                    Some(("SliderTickRate", value)) => {
+                        if let Ok(tick_rate) = value.parse::<f64>() {
+                            beatmap.slider_tick_rate = tick_rate.clamp(0.5, 8.0);
+                        }
+                    }
+                    _ => {}
+                },
+                Section::Events => {
+                    if let Some(period) = parse_break(line) {
+                        beatmap.breaks.push(period);
+                    }
+                }
+                Section::TimingPoints => {
+                    if let Some(point) = parse_timing_point(line) {
+                        beatmap.timing_points.push(point);
+                    }
+                }
+                Section::HitObjects => {
+                    if let Some(object) = parse_hit_object(line) {
+                        beatmap.hit_objects.push(object);
+                    }
+                }
+                Section::Colours | Section::Unknown => {}
+            }
+        }
+
+        // Stable sort, so points sharing a time keep their file order
+        beatmap
+            .timing_points
+            .sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        let durations: Vec<_> = beatmap
+            .hit_objects
+            .iter()
+            .map(|object| {
+                object.path.as_ref().map(|path| {
+                    beatmap.slider_duration(object.start_time, path.slides, path.length)
+                })
+            })
+            .collect();
+
+        for (object, duration) in beatmap.hit_objects.iter_mut().zip(durations) {
+            if let Some(duration) = duration {
+                object.end_time = object.start_time + duration;
+            }
+        }
+
+        beatmap
+            .hit_objects
+            .sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+        beatmap
+    }
+
+    /// Uninherited timing points, i.e. the ones carrying BPM information
+    pub(crate) fn uninherited_points(&self) -> impl Iterator<Item = &TimingPoint> {
+        self.timing_points.iter().filter(|point| point.uninherited)
+    }
+
+    /// Beat length of the uninherited timing point active at `time`
+    ///
+    /// Objects placed before the first timing point use the first one
+    pub(crate) fn beat_length_at(&self, time: f64) -> f64 {
+        let mut points = self.uninherited_points();
+
+        let Some(first) = points.next() else {
+            return DEFAULT_BEAT_LENGTH;
+        };
+
+        points
+            .take_while(|point| point.time <= time)
+            .last()
+            .unwrap_or(first)
+            .clamped_beat_length()
+    }
+
+    /// Slider velocity multiplier active at `time`
+    ///
+    /// An inherited point takes priority over an uninherited one at the same time
+    pub(crate) fn slider_velocity_at(&self, time: f64) -> f64 {
+        let mut velocity = 1.0;
+        let mut inherited_time = None;
+
+        for point in self.timing_points.iter() {
+            if point.time > time {
+                break;
+            }
+
+            if !point.uninherited {
+                velocity = point.slider_velocity();
+                inherited_time = Some(point.time);
+            } else if inherited_time != Some(point.time) {
+                velocity = 1.0;
+            }
+        }
+
+        velocity
+    }
+
+    fn slider_duration(&self, time: f64, slides: f64, length: f64) -> f64 {
+        let velocity =
+            BASE_SCORING_DISTANCE * self.slider_multiplier * self.slider_velocity_at(time)
+                / self.beat_length_at(time);
+
+        if velocity <= 0.0 {
+            return 0.0;
+        }
+
+        slides * length / velocity
+    }
+
+    /// Start time of the first hit object and end time of the last one
+    pub(crate) fn playable_bounds(&self) -> Option<(f64, f64)> {
+        let first = self.hit_objects.first()?.start_time;
+        let last = self
+            .hit_objects
+            .iter()
+            .map(|object| object.end_time)
+            .fold(f64::MIN, f64::max);
+
+        Some((first, last))
+    }
+}
+
+pub(crate) fn split_key_value(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+
+    Some((key.trim(), value.trim()))
+}
+
+fn parse_break(line: &str) -> Option<BreakPeriod> {
+    let mut fields = line.split(',').map(str::trim);
+
+    if !matches!(fields.next()?, "2" | "Break") {
+        return None;
+    }
+
+    let start_time = fields.next()?.parse().ok()?;
+    let end_time = fields.next()?.parse().ok()?;
+
+    Some(BreakPeriod {
+        start_time,
+        end_time,
+    })
+}
+
+fn parse_timing_point(line: &str) -> Option<TimingPoint> {
+    let fields: Vec<_> = line.split(',').map(str::trim).collect();
+
+    let time = fields.first()?.parse().ok()?;
+    let beat_length: f64 = fields.get(1)?.parse().ok()?;
+
+    if !beat_length.is_finite() {
+        return None;
+    }
+
+    let uninherited = fields
+        .get(6)
+        .and_then(|value| value.parse::<i32>().ok())
+        .is_none_or(|flag| flag != 0);
+    let kiai = fields
+        .get(7)
+        .and_then(|value| value.parse::<i32>().ok())
+        .is_some_and(|effects| effects & 1 != 0);
+
+    Some(TimingPoint {
+        time,
+        beat_length,
+        // Negative beat lengths are always inherited, whatever the flag says
+        uninherited: uninherited && beat_length > 0.0,
+        kiai,
+    })
+}
+
+/// Parses a hit object, sliders end at their start until their duration is known
+fn parse_hit_object(line: &str) -> Option<HitObject> {
+    let fields: Vec<_> = line.split(',').map(str::trim).collect();
+
+    let start_time: f64 = fields.get(2)?.parse().ok()?;
+    let kind_bits: i32 = fields.get(3)?.parse().ok()?;
+
+    let (kind, end_time, path) = if kind_bits & 2 != 0 {
+        let slides = fields.get(6)?.parse::<f64>().ok()?.max(1.0);
+        let length = fields.get(7)?.parse::<f64>().ok()?.max(0.0);
+
+        (
+            HitObjectKind::Slider,
+            start_time,
+            Some(SliderPath { slides, length }),
+        )
+    } else if kind_bits & 8 != 0 {
+        let end_time: f64 = fields.get(5)?.parse().ok()?;
+
+        (HitObjectKind::Spinner, end_time.max(start_time), None)
+    } else if kind_bits & 128 != 0 {
+        let end_time: f64 = fields.get(5)?.split(':').next()?.parse().ok()?;
+
+        (HitObjectKind::Hold, end_time.max(start_time), None)
+    } else {
+        (HitObjectKind::Circle, start_time, None)
+    };
+
+    Some(HitObject {
+        start_time,
+        end_time,
+        kind,
+        path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{DecodedBeatmap, HitObjectKind};
+    use crate::{ruleset::RulesetKind, utils::initialize_path};
+
+    fn decode() -> DecodedBeatmap {
+        DecodedBeatmap::from_bytes(&fs::read(initialize_path()).unwrap())
+    }
+
+    #[test]
+    fn test_decode_hit_object_counts() {
+        let beatmap = decode();
+        let count = |kind| {
+            beatmap
+                .hit_objects
+                .iter()
+                .filter(|object| object.kind == kind)
+                .count()
+        };
+
+        assert_eq!(count(HitObjectKind::Circle), 343);
+        assert_eq!(count(HitObjectKind::Slider), 177);
+        assert_eq!(count(HitObjectKind::Spinner), 2);
+    }
+
+    #[test]
+    fn test_decode_general_data() {
+        let beatmap = decode();
+
+        assert_eq!(beatmap.mode, RulesetKind::Osu);
+        assert_eq!(beatmap.version, "Expert");
+        assert_eq!(beatmap.slider_multiplier, 2.0);
+        assert_eq!(beatmap.bookmarks.len(), 14);
+        assert_eq!(beatmap.breaks.len(), 1);
+        assert_eq!(beatmap.uninherited_points().count(), 1);
+    }
+
+    #[test]
+    fn test_decode_slider_end_time() {
+        let beatmap = decode();
+        let slider = &beatmap.hit_objects[0];
+
+        // 100px at 2.0 multiplier and 1.0 SV is exactly half a beat
+        assert_eq!(slider.kind, HitObjectKind::Slider);
+        assert!((slider.end_time - slider.start_time - 428.571428571429 / 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,688 @@
+//! Pre-flight validation of .osu files
+//!
+//! osu-native only reports that a beatmap failed to load, not why. [`lint`]
+//! checks the parts of the format that most commonly break parsing and points
+//! at the offending line and column
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::beatmap::lint::lint;
+//! let text = std::fs::read_to_string("map.osu")?;
+//! for diagnostic in lint(&text) {
+//!     eprintln!("map.osu:{diagnostic}");
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{collections::HashSet, fmt};
+
+use crate::beatmap::decode::{Section, split_key_value};
+
+const FORMAT_HEADER: &str = "osu file format v";
+
+/// Newest format version written by osu!
+const LATEST_FORMAT_VERSION: i32 = 14;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Suspicious, but osu! will still load the file
+    Warning,
+    /// Will most likely make loading the file fail
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A single problem found by [`lint`]
+///
+/// Lines and columns are 1-based, columns count characters
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, self.severity, self.message
+        )
+    }
+}
+
+/// Checks the contents of an .osu file, returning diagnostics in line order
+///
+/// Covers the format version header, section structure, numeric fields in
+/// [General] and [Difficulty], timing points and hit object syntax
+pub fn lint(text: &str) -> Vec<Diagnostic> {
+    let mut linter = Linter::default();
+    linter.run(text);
+
+    linter.diagnostics
+}
+
+#[derive(Default)]
+struct Linter {
+    diagnostics: Vec<Diagnostic>,
+    /// 1-based number of the line being checked
+    line: usize,
+    /// Raw contents of the line being checked
+    raw: String,
+}
+
+impl Linter {
+    fn run(&mut self, text: &str) {
+        let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+        let mut header_checked = false;
+        let mut section = None;
+        let mut seen = HashSet::new();
+        let mut has_uninherited_point = false;
+        let mut hit_objects = 0;
+
+        for (i, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with("//") {
+                continue;
+            }
+
+            self.start_line(i, line);
+            let column = indent(line) + 1;
+
+            if !header_checked {
+                header_checked = true;
+
+                // Anything else is linted as regular content after reporting it
+                if self.check_header(line) {
+                    continue;
+                }
+            }
+
+            if trimmed.starts_with('[') {
+                let Some(next) = Section::from_header(trimmed) else {
+                    self.push(column, Severity::Error, "Unterminated section header");
+                    section = Some(Section::Unknown);
+                    continue;
+                };
+
+                if next == Section::Unknown {
+                    self.push(
+                        column,
+                        Severity::Warning,
+                        format!("Unknown section {trimmed}"),
+                    );
+                } else if !seen.insert(next) {
+                    self.push(
+                        column,
+                        Severity::Warning,
+                        format!("Duplicate section {trimmed}"),
+                    );
+                }
+
+                section = Some(next);
+                continue;
+            }
+
+            match section {
+                None => self.push(column, Severity::Error, "Content outside of any section"),
+                Some(Section::General) => self.check_general(line),
+                Some(Section::Difficulty) => self.check_difficulty(line),
+                Some(Section::Editor | Section::Metadata) => {
+                    self.check_key_value(line);
+                }
+                Some(Section::TimingPoints) => {
+                    has_uninherited_point |= self.check_timing_point(line);
+                }
+                Some(Section::HitObjects) => {
+                    self.check_hit_object(line);
+                    hit_objects += 1;
+                }
+                Some(Section::Events | Section::Colours | Section::Unknown) => {}
+            }
+        }
+
+        if !header_checked {
+            self.line = 1;
+            self.push(1, Severity::Error, "File is empty");
+            return;
+        }
+
+        self.line = text.lines().count();
+
+        if !seen.contains(&Section::HitObjects) {
+            self.push(1, Severity::Error, "Missing [HitObjects] section");
+        } else if hit_objects == 0 {
+            self.push(1, Severity::Warning, "Beatmap has no hit objects");
+        }
+
+        if hit_objects > 0 && !has_uninherited_point {
+            self.push(
+                1,
+                Severity::Warning,
+                "Beatmap has no uninherited timing point, 60 BPM is assumed",
+            );
+        }
+    }
+
+    fn start_line(&mut self, index: usize, line: &str) {
+        self.line = index + 1;
+        line.clone_into(&mut self.raw);
+    }
+
+    fn push(&mut self, column: usize, severity: Severity, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            line: self.line,
+            column,
+            severity,
+            message: message.into(),
+        });
+    }
+
+    /// Column of a field given its byte offset in the raw line
+    fn column_at(&self, offset: usize) -> usize {
+        self.raw[..offset].chars().count() + 1
+    }
+
+    /// Returns whether the line is a format header, valid or not
+    fn check_header(&mut self, line: &str) -> bool {
+        let column = indent(line) + 1;
+
+        let Some(version) = line.trim().strip_prefix(FORMAT_HEADER) else {
+            self.push(
+                column,
+                Severity::Error,
+                format!("Expected \"{FORMAT_HEADER}<version>\" on the first line"),
+            );
+            return false;
+        };
+
+        let column = column + FORMAT_HEADER.len();
+
+        match version.parse::<i32>() {
+            Ok(version) if version > LATEST_FORMAT_VERSION => self.push(
+                column,
+                Severity::Warning,
+                format!("Format version {version} is newer than v{LATEST_FORMAT_VERSION}"),
+            ),
+            Ok(_) => {}
+            Err(_) => self.push(
+                column,
+                Severity::Error,
+                format!("Invalid format version {version:?}"),
+            ),
+        }
+
+        true
+    }
+
+    /// Returns the key, the value and the byte offset of the value
+    fn check_key_value<'a>(&mut self, line: &'a str) -> Option<(&'a str, &'a str, usize)> {
+        let Some((key, value)) = split_key_value(line) else {
+            self.push(
+                indent(line) + 1,
+                Severity::Error,
+                "Expected a \"Key: Value\" pair",
+            );
+            return None;
+        };
+
+        let colon = line.find(':').unwrap_or_default();
+        let offset = colon + 1 + indent(&line[colon + 1..]);
+
+        Some((key, value, offset))
+    }
+
+    fn check_general(&mut self, line: &str) {
+        let Some((key, value, offset)) = self.check_key_value(line) else {
+            return;
+        };
+
+        let column = self.column_at(offset);
+
+        match key {
+            "Mode" if !matches!(value.parse::<i32>(), Ok(0..=3)) => {
+                self.push(
+                    column,
+                    Severity::Error,
+                    format!("Mode must be 0, 1, 2 or 3, got {value:?}"),
+                );
+            }
+            "AudioLeadIn" | "PreviewTime" | "Countdown" => {
+                self.check_number::<i32>(column, key, value);
+            }
+            "StackLeniency" => {
+                self.check_number::<f32>(column, key, value);
+            }
+            _ => {}
+        }
+    }
+
+    fn check_difficulty(&mut self, line: &str) {
+        let Some((key, value, offset)) = self.check_key_value(line) else {
+            return;
+        };
+
+        let column = self.column_at(offset);
+
+        let range = match key {
+            "HPDrainRate" | "CircleSize" | "OverallDifficulty" | "ApproachRate" => 0.0..=10.0,
+            "SliderMultiplier" => 0.4..=3.6,
+            "SliderTickRate" => 0.5..=8.0,
+            _ => return,
+        };
+
+        if let Some(number) = self.check_number::<f64>(column, key, value)
+            && !range.contains(&number)
+        {
+            self.push(
+                column,
+                Severity::Warning,
+                format!(
+                    "{key} {number} is outside of {}..={} and will be clamped",
+                    range.start(),
+                    range.end()
+                ),
+            );
+        }
+    }
+
+    /// Returns whether the line is a valid uninherited timing point
+    fn check_timing_point(&mut self, line: &str) -> bool {
+        let fields = split_fields(line, ',');
+
+        if fields.len() < 2 {
+            self.push(
+                indent(line) + 1,
+                Severity::Error,
+                "Timing point needs at least a time and a beat length",
+            );
+            return false;
+        }
+
+        let mut valid = true;
+
+        let time = self.check_field::<f64>(fields[0], "time");
+        let beat_length = self.check_field::<f64>(fields[1], "beat length");
+        valid &= time.is_some();
+
+        if let Some(beat_length) = beat_length {
+            if beat_length == 0.0 {
+                self.push(
+                    self.column_at(fields[1].0),
+                    Severity::Warning,
+                    "Beat length of 0 is ignored",
+                );
+            }
+        } else {
+            valid = false;
+        }
+
+        let names = [
+            "meter",
+            "sample set",
+            "sample index",
+            "volume",
+            "uninherited flag",
+            "effects",
+        ];
+
+        for (field, name) in fields[2..].iter().zip(names) {
+            valid &= self.check_field::<i32>(*field, name).is_some();
+        }
+
+        let uninherited = fields
+            .get(6)
+            .and_then(|(_, flag)| flag.parse::<i32>().ok())
+            .is_none_or(|flag| flag != 0);
+
+        valid && uninherited && beat_length.is_some_and(|beat_length| beat_length > 0.0)
+    }
+
+    fn check_hit_object(&mut self, line: &str) {
+        let fields = split_fields(line, ',');
+
+        if fields.len() < 5 {
+            self.push(
+                indent(line) + 1,
+                Severity::Error,
+                "Hit object needs at least x, y, time, type and hitsound",
+            );
+            return;
+        }
+
+        self.check_field::<f32>(fields[0], "x");
+        self.check_field::<f32>(fields[1], "y");
+        let start_time = self.check_field::<f64>(fields[2], "time");
+
+        let Some(kind) = self.check_field::<i32>(fields[3], "type") else {
+            return;
+        };
+
+        self.check_field::<i32>(fields[4], "hitsound");
+
+        let type_column = self.column_at(fields[3].0);
+        let type_bits = [kind & 1, kind & 2, kind & 8, kind & 128];
+
+        match type_bits.iter().filter(|bit| **bit != 0).count() {
+            0 => {
+                self.push(
+                    type_column,
+                    Severity::Error,
+                    format!("Type {kind} is not a circle, slider, spinner or hold"),
+                );
+                return;
+            }
+            1 => {}
+            _ => self.push(
+                type_column,
+                Severity::Warning,
+                format!("Type {kind} sets more than one object type"),
+            ),
+        }
+
+        if kind & 2 != 0 {
+            self.check_slider(line, &fields);
+        } else if kind & 8 != 0 {
+            self.check_end_time(line, &fields, start_time, "Spinner", fields.get(5).copied());
+        } else if kind & 128 != 0 {
+            let end_time = fields
+                .get(5)
+                .map(|(offset, value)| (*offset, value.split(':').next().unwrap_or_default()));
+
+            self.check_end_time(line, &fields, start_time, "Hold note", end_time);
+        }
+    }
+
+    fn check_slider(&mut self, line: &str, fields: &[(usize, &str)]) {
+        if fields.len() < 8 {
+            self.push(
+                self.column_at(line.trim_end().len()),
+                Severity::Error,
+                "Slider needs a curve, slide count and length",
+            );
+            return;
+        }
+
+        let (curve_offset, curve) = fields[5];
+        let mut points = curve.split('|');
+        let curve_type = points.next().unwrap_or_default();
+
+        if !matches!(curve_type, "B" | "C" | "L" | "P") {
+            self.push(
+                self.column_at(curve_offset),
+                Severity::Error,
+                format!("Unknown slider curve type {curve_type:?}"),
+            );
+        }
+
+        let mut offset = curve_offset + curve_type.len() + 1;
+
+        for point in points {
+            let valid = point
+                .split_once(':')
+                .is_some_and(|(x, y)| x.parse::<f32>().is_ok() && y.parse::<f32>().is_ok());
+
+            if !valid {
+                self.push(
+                    self.column_at(offset),
+                    Severity::Error,
+                    format!("Invalid slider control point {point:?}"),
+                );
+            }
+
+            offset += point.len() + 1;
+        }
+
+        if let Some(slides) = self.check_field::<i32>(fields[6], "slide count")
+            && slides < 1
+        {
+            self.push(
+                self.column_at(fields[6].0),
+                Severity::Error,
+                "Slide count must be at least 1",
+            );
+        }
+
+        if let Some(length) = self.check_field::<f64>(fields[7], "length")
+            && length < 0.0
+        {
+            self.push(
+                self.column_at(fields[7].0),
+                Severity::Warning,
+                "Negative slider length is treated as 0",
+            );
+        }
+    }
+
+    fn check_end_time(
+        &mut self,
+        line: &str,
+        fields: &[(usize, &str)],
+        start_time: Option<f64>,
+        kind: &str,
+        end_time: Option<(usize, &str)>,
+    ) {
+        let Some(end_time) = end_time else {
+            let last = fields
+                .last()
+                .map_or(0, |(offset, value)| offset + value.len());
+            self.push(
+                self.column_at(last.min(line.len())),
+                Severity::Error,
+                format!("{kind} is missing its end time"),
+            );
+            return;
+        };
+
+        if let Some(end) = self.check_field::<f64>(end_time, "end time")
+            && start_time.is_some_and(|start| end < start)
+        {
+            self.push(
+                self.column_at(end_time.0),
+                Severity::Warning,
+                format!("{kind} ends before it starts"),
+            );
+        }
+    }
+
+    fn check_field<T: std::str::FromStr>(
+        &mut self,
+        (offset, value): (usize, &str),
+        name: &str,
+    ) -> Option<T> {
+        self.check_number(self.column_at(offset), name, value)
+    }
+
+    fn check_number<T: std::str::FromStr>(
+        &mut self,
+        column: usize,
+        name: &str,
+        value: &str,
+    ) -> Option<T> {
+        let number = value.parse().ok();
+
+        if number.is_none() {
+            self.push(
+                column,
+                Severity::Error,
+                format!("Expected a number for {name}, got {value:?}"),
+            );
+        }
+
+        number
+    }
+}
+
+/// Splits a line into trimmed fields paired with their byte offset
+fn split_fields(line: &str, separator: char) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+
+    line.split(separator)
+        .map(|field| {
+            let start = offset + indent(field);
+            offset += field.len() + separator.len_utf8();
+
+            (start, field.trim())
+        })
+        .collect()
+}
+
+/// Byte length of leading whitespace
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Diagnostic, Severity, lint};
+    use crate::{
+        beatmap::{Beatmap, BeatmapError},
+        utils::initialize_path,
+    };
+
+    const MINIMAL: &str = "osu file format v14
+
+[General]
+Mode: 0
+
+[Difficulty]
+CircleSize:4
+SliderMultiplier:1.4
+
+[TimingPoints]
+0,500,4,2,0,100,1,0
+
+[HitObjects]
+256,192,1000,1,0,0:0:0:0:
+";
+
+    fn errors(text: &str) -> Vec<Diagnostic> {
+        lint(text)
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_valid_files() {
+        let file = fs::read_to_string(initialize_path()).unwrap();
+
+        assert_eq!(lint(&file), []);
+        assert_eq!(lint(MINIMAL), []);
+    }
+
+    #[test]
+    fn test_lint_format_header() {
+        let diagnostics = errors("\u{FEFF}  osu file format vX\n[HitObjects]\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (1, 20));
+
+        let diagnostics = errors("[General]\nMode: 0\n[HitObjects]\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (1, 1));
+
+        assert_eq!(errors("\n\n")[0].message, "File is empty");
+    }
+
+    #[test]
+    fn test_lint_sections() {
+        let text = MINIMAL.replace("[TimingPoints]", "[TimingPoints");
+        let diagnostics = errors(&text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 10);
+        assert_eq!(diagnostics[0].message, "Unterminated section header");
+
+        let text = MINIMAL.replace("[General]", "[Genral]");
+        let diagnostics = lint(&text);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+
+        let text = MINIMAL.replace("\n\n[General]", "\nMode: 0\n[General]");
+        let diagnostics = errors(&text);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 2);
+        assert_eq!(diagnostics[0].message, "Content outside of any section");
+    }
+
+    #[test]
+    fn test_lint_numeric_fields() {
+        let text = MINIMAL
+            .replace("Mode: 0", "Mode: 7")
+            .replace("CircleSize:4", "CircleSize:four")
+            .replace("SliderMultiplier:1.4", "SliderMultiplier:5");
+        let diagnostics = lint(&text);
+
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (4, 7));
+        assert_eq!((diagnostics[1].line, diagnostics[1].column), (7, 12));
+        assert_eq!(diagnostics[2].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_lint_timing_points() {
+        let text = MINIMAL.replace("0,500,4,2,0,100,1,0", "0, abc,4,2,0,100,1,0");
+        let diagnostics = lint(&text);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (11, 4));
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_lint_hit_objects() {
+        let text = format!(
+            "{MINIMAL}256,192,1500,2,0,X|300:200,1,100\n\
+             256,192,2000,12,0\n\
+             256,192,2500,128,0,2000:0:0:0:\n\
+             256,192,3000,0,0\n"
+        );
+        let diagnostics = lint(&text);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.severity))
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                (15, 18, Severity::Error),
+                (16, 18, Severity::Error),
+                (17, 20, Severity::Warning),
+                (18, 14, Severity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_text_checked() {
+        let text = MINIMAL.replace("256,192,1000,1,0", "256,192,soon,1,0");
+
+        assert!(matches!(
+            Beatmap::from_text_checked(text),
+            Err(BeatmapError::Invalid(diagnostics)) if diagnostics.len() == 1
+        ));
+
+        let file = fs::read_to_string(initialize_path()).unwrap();
+        assert!(Beatmap::from_text_checked(file).is_ok());
+    }
+}
@@ -1,32 +1,88 @@
-use std::{ffi::CString, mem::MaybeUninit, path::Path};
+use std::{
+    ffi::CString,
+    fs,
+    io::{Error as IoError, ErrorKind},
+    mem::MaybeUninit,
+    path::Path,
+    sync::OnceLock,
+};
 
 use libosu_native_sys::{
-    Beatmap_CreateFromFile, Beatmap_CreateFromText, Beatmap_Destroy, Beatmap_GetArtist,
-    Beatmap_GetTitle, Beatmap_GetVersion, ErrorCode, NativeBeatmap,
+    Beatmap_CreateFromText, Beatmap_Destroy, Beatmap_GetArtist, Beatmap_GetTitle,
+    Beatmap_GetVersion, ErrorCode, NativeBeatmap,
 };
 use thiserror::Error as ThisError;
 
 use crate::{
+    beatmap::{
+        attributes::{BaseDifficulty, BeatmapAttributes},
+        checksum::md5_hex,
+        decode::DecodedBeatmap,
+        lint::{Diagnostic, lint},
+        statistics::BeatmapStatistics,
+    },
     error::NativeError,
+    mods::{GameModsError, IntoGameMods},
+    ruleset::{InvalidRulesetId, RulesetKind},
     utils::{HasNative, NativeType, StringError, read_native_string},
 };
 
+pub mod archive;
+pub mod attributes;
+pub mod builder;
+pub mod checksum;
+pub(crate) mod decode;
+pub mod lint;
+pub mod statistics;
+
 /// osu! Beatmap. Contains general ruleset-independent attributes
 /// Also contains methods for getting extra data
 pub struct Beatmap {
     handle: i32,
+    ruleset_id: i32,
     pub approach_rate: f32,
     pub drain_rate: f32,
     pub overall_difficulty: f32,
     pub circle_size: f32,
     pub slider_multiplier: f64,
     pub slider_tick_rate: f64,
+    source: Vec<u8>,
+    checksum: OnceLock<String>,
+    decoded: OnceLock<DecodedBeatmap>,
 }
 
 impl Beatmap {
     pub fn handle(&self) -> i32 {
         self.handle
     }
+
+    /// The ruleset the beatmap was made for
+    ///
+    /// # Errors
+    /// Returns an [`InvalidRulesetId`] if osu-native reports an unknown ruleset
+    pub fn ruleset(&self) -> Result<RulesetKind, InvalidRulesetId> {
+        self.ruleset_id.try_into()
+    }
+
+    /// Raw contents of the .osu file this beatmap was loaded from
+    pub fn source(&self) -> &[u8] {
+        &self.source
+    }
+
+    /// MD5 checksum of the loaded .osu file, as referenced by scores and replays
+    ///
+    /// Computed on first access
+    pub fn checksum(&self) -> &str {
+        self.checksum.get_or_init(|| md5_hex(&self.source))
+    }
+
+    /// The Rust-side reading of the loaded file
+    ///
+    /// Decoded on first access
+    pub(crate) fn decoded(&self) -> &DecodedBeatmap {
+        self.decoded
+            .get_or_init(|| DecodedBeatmap::from_bytes(&self.source))
+    }
 }
 
 impl Drop for Beatmap {
@@ -44,12 +100,16 @@ impl From<NativeBeatmap> for Beatmap {
     fn from(value: NativeBeatmap) -> Self {
         Self {
             handle: value.handle,
+            ruleset_id: value.ruleset_id,
             approach_rate: value.approach_rate,
             drain_rate: value.drain_rate,
             overall_difficulty: value.overall_difficulty,
             circle_size: value.circle_size,
             slider_multiplier: value.slider_multiplier,
             slider_tick_rate: value.slider_tick_rate,
+            source: Vec::new(),
+            checksum: OnceLock::new(),
+            decoded: OnceLock::new(),
         }
     }
 }
@@ -58,6 +118,12 @@ impl From<NativeBeatmap> for Beatmap {
 pub enum BeatmapError {
     #[error("Specified string is invalid")]
     StringError,
+    #[error("Failed to read beatmap file")]
+    Io(#[from] IoError),
+    #[error("Game mods error")]
+    Mods(#[from] GameModsError),
+    #[error("Beatmap failed validation with {} error(s)", .0.len())]
+    Invalid(Vec<Diagnostic>),
     #[error("Native error")]
     Native(#[from] NativeError),
 }
@@ -80,25 +146,21 @@ impl Beatmap {
     /// println!("{}", beatmap.approach_rate);
     /// ```
     ///
+    /// The file is read once and its contents are passed to osu-native, so the
+    /// native beatmap and e.g. [`Beatmap::checksum`] always describe the same bytes
+    ///
     /// # Errors
-    /// Returns a [`BeatmapError::StringError`] if the path can't be correctly converted to [`CString`]
+    /// Returns a [`NativeError::BeatmapFileNotFound`] if the file doesn't exist
+    /// Returns a [`BeatmapError::Io`] if the file can't be read
+    /// Returns a [`BeatmapError::StringError`] if the file contains a nul byte
     /// Returns a [`BeatmapError::NativeError`] if there is an error on osu-native's side
     pub fn from_path(path: impl AsRef<Path>) -> Result<Self, BeatmapError> {
-        let Some(Ok(path_cstr)) = path.as_ref().to_str().map(CString::new) else {
-            return Err(BeatmapError::StringError);
-        };
-
-        let mut beatmap: MaybeUninit<NativeType<Self>> = MaybeUninit::uninit();
+        let bytes = fs::read(path).map_err(|err| match err.kind() {
+            ErrorKind::NotFound => BeatmapError::Native(NativeError::BeatmapFileNotFound),
+            _ => BeatmapError::Io(err),
+        })?;
 
-        let code = unsafe { Beatmap_CreateFromFile(path_cstr.as_ptr(), beatmap.as_mut_ptr()) };
-
-        if code != ErrorCode::Success {
-            return Err(code.into());
-        }
-
-        let native = unsafe { beatmap.assume_init() };
-
-        Ok(native.into())
+        Self::from_bytes(bytes)
     }
 
     /// Creates a new [`Beatmap`] from an .osu file in a [`String`]
@@ -116,7 +178,11 @@ impl Beatmap {
     /// Returns a [`BeatmapError::StringError`] if the path can't be correctly converted to [`CString`]
     /// Returns a [`BeatmapError::NativeError`] if there is an error on osu-native's side
     pub fn from_text(string: String) -> Result<Self, BeatmapError> {
-        let Ok(map_cstr) = CString::new(string) else {
+        Self::from_bytes(string.into_bytes())
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, BeatmapError> {
+        let Ok(map_cstr) = CString::new(bytes.as_slice()) else {
             return Err(BeatmapError::StringError);
         };
 
@@ -129,8 +195,44 @@ impl Beatmap {
         }
 
         let native = unsafe { beatmap.assume_init() };
+        let mut beatmap: Self = native.into();
+        beatmap.source = bytes;
 
-        Ok(native.into())
+        Ok(beatmap)
+    }
+
+    /// Same as [`Beatmap::from_text`], but runs [`lint`] first and refuses to
+    /// pass the file to osu-native if it reports any errors
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use osu_native::beatmap::{Beatmap, BeatmapError};
+    /// # let text = String::new();
+    /// match Beatmap::from_text_checked(text) {
+    ///     Ok(beatmap) => println!("{}", beatmap.approach_rate),
+    ///     Err(BeatmapError::Invalid(diagnostics)) => {
+    ///         for diagnostic in diagnostics {
+    ///             eprintln!("{diagnostic}");
+    ///         }
+    ///     }
+    ///     Err(err) => eprintln!("{err}"),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a [`BeatmapError::Invalid`] with the error diagnostics if linting fails
+    /// Otherwise see [`Beatmap::from_text`]
+    pub fn from_text_checked(string: String) -> Result<Self, BeatmapError> {
+        let errors: Vec<_> = lint(&string)
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .collect();
+
+        if !errors.is_empty() {
+            return Err(BeatmapError::Invalid(errors));
+        }
+
+        Self::from_text(string)
     }
 
     /// Creates a [`String`] with beatmap's romanized title by fetching it from osu-native
@@ -183,16 +285,73 @@ impl Beatmap {
     pub fn version(&self) -> Result<String, StringError> {
         read_native_string(self.handle, Beatmap_GetVersion)
     }
+
+    /// Calculates BPM, total length and drain time of the beatmap under `mods`
+    ///
+    /// Computed on the Rust side from the timing points, hit objects and breaks
+    /// of the loaded file, see [`BeatmapStatistics`]
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use osu_native::beatmap::Beatmap;
+    /// # let path = "../../standard.osu";
+    /// # let beatmap = Beatmap::from_path(path).unwrap();
+    /// let statistics = beatmap.statistics(0).unwrap();
+    /// println!("{} BPM, {}ms", statistics.most_common_bpm, statistics.drain_time);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a [`BeatmapError::Mods`] if `mods` can't be converted to [`GameMods`](crate::mods::GameMods)
+    pub fn statistics(&self, mods: impl IntoGameMods) -> Result<BeatmapStatistics, BeatmapError> {
+        let clock_rate = mods.into_mods()?.clock_rate();
+        Ok(BeatmapStatistics::new(self.decoded(), clock_rate))
+    }
+
+    /// Calculates AR, OD, CS, HP, approach preempt and hit windows of the beatmap
+    /// when played in `ruleset` with `mods`
+    ///
+    /// See [`BeatmapAttributes`] for how mods are applied
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use osu_native::{beatmap::Beatmap, ruleset::RulesetKind};
+    /// # let path = "../../standard.osu";
+    /// # let beatmap = Beatmap::from_path(path).unwrap();
+    /// // DT
+    /// let attributes = beatmap.attributes(RulesetKind::Osu, 64).unwrap();
+    /// println!("AR{:.2} OD{:.2}", attributes.approach_rate, attributes.overall_difficulty);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a [`BeatmapError::Mods`] if `mods` can't be converted to [`GameMods`](crate::mods::GameMods)
+    pub fn attributes(
+        &self,
+        ruleset: RulesetKind,
+        mods: impl IntoGameMods,
+    ) -> Result<BeatmapAttributes, BeatmapError> {
+        let mods = mods.into_mods()?;
+        let base = BaseDifficulty {
+            approach_rate: self.approach_rate.into(),
+            overall_difficulty: self.overall_difficulty.into(),
+            circle_size: self.circle_size.into(),
+            drain_rate: self.drain_rate.into(),
+        };
+        let is_convert = self.decoded().mode != ruleset;
+
+        Ok(BeatmapAttributes::new(base, ruleset, &mods, is_convert))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs::File;
 
+    use crate::ruleset::RulesetKind;
     use crate::utils::initialize_path;
     use crate::{generate_beatmap_field_tests, generate_beatmap_method_tests};
 
-    use super::Beatmap;
+    use super::{Beatmap, BeatmapError};
+    use crate::error::NativeError;
     generate_beatmap_field_tests! {
         approach_rate == 9.2,
         overall_difficulty == 8.3,
@@ -206,5 +365,14 @@ mod tests {
         title() == "Toy Box",
         artist() == "John Grant",
         version() == "Expert",
+        ruleset() == RulesetKind::Osu,
+    }
+
+    #[test]
+    fn test_from_path_missing_file() {
+        assert!(matches!(
+            Beatmap::from_path("missing.osu"),
+            Err(BeatmapError::Native(NativeError::BeatmapFileNotFound))
+        ));
     }
 }
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use crate::beatmap::decode::{DEFAULT_BEAT_LENGTH, DecodedBeatmap};
+
+/// BPM and length statistics of a beatmap, as shown on beatmap cards
+///
+/// All values are already adjusted for the clock rate of the mods they were
+/// calculated with. Lengths are in milliseconds
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeatmapStatistics {
+    pub clock_rate: f64,
+    pub min_bpm: f64,
+    pub max_bpm: f64,
+    pub most_common_bpm: f64,
+    pub total_length: f64,
+    pub drain_time: f64,
+}
+
+impl BeatmapStatistics {
+    pub(crate) fn new(beatmap: &DecodedBeatmap, clock_rate: f64) -> Self {
+        let beat_lengths = || {
+            beatmap
+                .uninherited_points()
+                .map(|point| point.clamped_beat_length())
+        };
+
+        let longest_beat = beat_lengths()
+            .reduce(f64::max)
+            .unwrap_or(DEFAULT_BEAT_LENGTH);
+        let shortest_beat = beat_lengths()
+            .reduce(f64::min)
+            .unwrap_or(DEFAULT_BEAT_LENGTH);
+
+        let (first_time, last_time) = beatmap.playable_bounds().unwrap_or_default();
+        let total_length = last_time - first_time;
+        let break_time: f64 = beatmap.breaks.iter().map(|period| period.duration()).sum();
+
+        Self {
+            clock_rate,
+            min_bpm: 60000.0 / longest_beat * clock_rate,
+            max_bpm: 60000.0 / shortest_beat * clock_rate,
+            most_common_bpm: 60000.0 / most_common_beat_length(beatmap) * clock_rate,
+            total_length: total_length / clock_rate,
+            drain_time: (total_length - break_time).max(0.0) / clock_rate,
+        }
+    }
+}
+
+/// Beat length that is active for the longest time, same as lazer's
+/// `Beatmap.GetMostCommonBeatLength`
+fn most_common_beat_length(beatmap: &DecodedBeatmap) -> f64 {
+    let points: Vec<_> = beatmap.uninherited_points().collect();
+
+    let last_time = match beatmap.playable_bounds() {
+        Some((_, last_time)) => last_time,
+        None => points.last().map_or(0.0, |point| point.time),
+    };
+
+    // Keyed by beat length rounded to 3 decimal places to group near-identical points,
+    // values are the index of the group's first point, its beat length and its duration
+    let mut durations: HashMap<i64, (usize, f64, f64)> = HashMap::new();
+
+    for (i, point) in points.iter().enumerate() {
+        let duration = if point.time > last_time {
+            0.0
+        } else {
+            let start = if i == 0 { 0.0 } else { point.time };
+            let end = points.get(i + 1).map_or(last_time, |next| next.time);
+
+            end - start
+        };
+
+        let beat_length = point.clamped_beat_length();
+        let entry = durations
+            .entry((beat_length * 1000.0).round() as i64)
+            .or_insert((i, beat_length, 0.0));
+        entry.2 += duration;
+    }
+
+    // Ties go to the earliest timing point, like lazer's ordered grouping
+    durations
+        .into_values()
+        .max_by(|(i, _, a), (j, _, b)| a.total_cmp(b).then(j.cmp(i)))
+        .map_or(DEFAULT_BEAT_LENGTH, |(_, beat_length, _)| beat_length)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rosu_mods::{Acronym, GameModSimple, simple::SettingSimple};
+
+    use super::BeatmapStatistics;
+    use crate::{
+        beatmap::{Beatmap, decode::DecodedBeatmap},
+        utils::initialize_path,
+    };
+
+    fn assert_approx(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "Expected {expected}, but got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_statistics_no_mods() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let statistics = beatmap.statistics(vec![]).unwrap();
+
+        assert_eq!(statistics.clock_rate, 1.0);
+        assert_approx(statistics.min_bpm, 140.0);
+        assert_approx(statistics.max_bpm, 140.0);
+        assert_approx(statistics.most_common_bpm, 140.0);
+        assert_approx(statistics.total_length, 121178.0);
+        assert_approx(statistics.drain_time, 121178.0 - 6087.0);
+    }
+
+    #[test]
+    fn test_statistics_double_time() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let nomod = beatmap.statistics(vec![]).unwrap();
+        let statistics = beatmap
+            .statistics(vec![GameModSimple {
+                acronym: Acronym::from_str("DT").unwrap(),
+                settings: Default::default(),
+            }])
+            .unwrap();
+
+        assert_eq!(statistics.clock_rate, 1.5);
+        assert_approx(statistics.most_common_bpm, 210.0);
+        assert_approx(statistics.total_length, nomod.total_length / 1.5);
+        assert_approx(statistics.drain_time, nomod.drain_time / 1.5);
+    }
+
+    #[test]
+    fn test_statistics_custom_rate() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let statistics = beatmap
+            .statistics(vec![GameModSimple {
+                acronym: Acronym::from_str("HT").unwrap(),
+                settings: [("speed_change".into(), SettingSimple::Number(0.6))]
+                    .into_iter()
+                    .collect(),
+            }])
+            .unwrap();
+
+        assert_eq!(statistics.clock_rate, 0.6);
+        assert_approx(statistics.most_common_bpm, 84.0);
+    }
+
+    #[test]
+    fn test_most_common_bpm_tie() {
+        // Both timing points are active for exactly one second
+        let beatmap = DecodedBeatmap::from_text(
+            "[TimingPoints]\n0,500,4,2,0,100,1,0\n1000,250,4,2,0,100,1,0\n\
+             [HitObjects]\n256,192,0,1,0\n256,192,2000,1,0\n",
+        );
+
+        for _ in 0..10 {
+            assert_approx(BeatmapStatistics::new(&beatmap, 1.0).most_common_bpm, 120.0);
+        }
+    }
+}
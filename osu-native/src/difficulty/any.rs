@@ -0,0 +1,293 @@
+use crate::{
+    beatmap::Beatmap,
+    difficulty::{
+        DifficultyCalculator,
+        catch::{CatchDifficultyAttributes, CatchDifficultyCalculator},
+        mania::{ManiaDifficultyAttributes, ManiaDifficultyCalculator},
+        osu::{OsuDifficultyAttributes, OsuDifficultyCalculator},
+        taiko::{TaikoDifficultyAttributes, TaikoDifficultyCalculator},
+    },
+    error::OsuError,
+    mods::{GameMods, IntoGameMods},
+    ruleset::{Ruleset, RulesetKind, RulesetMismatch},
+};
+
+/// Difficulty calculator for a ruleset only known at runtime
+///
+/// # Examples
+/// ```no_run
+/// # use osu_native::{beatmap::Beatmap, difficulty::any::AnyDifficultyCalculator};
+/// # let beatmap = Beatmap::from_path("../../standard.osu")?;
+/// let kind = beatmap.ruleset()?;
+/// let attributes = AnyDifficultyCalculator::from_kind(kind, &beatmap)?
+///     .with_mods(64)?
+///     .calculate()?;
+/// println!("{:.2}* ({}x)", attributes.star_rating(), attributes.max_combo());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub enum AnyDifficultyCalculator {
+    Osu(OsuDifficultyCalculator),
+    Taiko(TaikoDifficultyCalculator),
+    Catch(CatchDifficultyCalculator),
+    Mania(ManiaDifficultyCalculator),
+}
+
+impl AnyDifficultyCalculator {
+    /// Creates the calculator matching the kind of `ruleset`
+    ///
+    /// # Errors
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    pub fn new(ruleset: Ruleset, beatmap: &Beatmap) -> Result<Self, OsuError> {
+        let calculator = match ruleset.kind {
+            RulesetKind::Osu => Self::Osu(OsuDifficultyCalculator::new(ruleset, beatmap)?),
+            RulesetKind::Taiko => Self::Taiko(TaikoDifficultyCalculator::new(ruleset, beatmap)?),
+            RulesetKind::Catch => Self::Catch(CatchDifficultyCalculator::new(ruleset, beatmap)?),
+            RulesetKind::Mania => Self::Mania(ManiaDifficultyCalculator::new(ruleset, beatmap)?),
+        };
+
+        Ok(calculator)
+    }
+
+    /// Creates the ruleset for `kind` and the matching calculator
+    ///
+    /// # Errors
+    /// Returns [`OsuError::Ruleset`] if the ruleset can't be created
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    pub fn from_kind(kind: RulesetKind, beatmap: &Beatmap) -> Result<Self, OsuError> {
+        Self::new(Ruleset::new(kind)?, beatmap)
+    }
+
+    pub fn ruleset(&self) -> RulesetKind {
+        match self {
+            Self::Osu(_) => RulesetKind::Osu,
+            Self::Taiko(_) => RulesetKind::Taiko,
+            Self::Catch(_) => RulesetKind::Catch,
+            Self::Mania(_) => RulesetKind::Mania,
+        }
+    }
+
+    pub fn mods(&self) -> &GameMods {
+        match self {
+            Self::Osu(calculator) => calculator.mods(),
+            Self::Taiko(calculator) => calculator.mods(),
+            Self::Catch(calculator) => calculator.mods(),
+            Self::Mania(calculator) => calculator.mods(),
+        }
+    }
+
+    pub fn with_mods(self, mods: impl IntoGameMods) -> Result<Self, OsuError> {
+        let calculator = match self {
+            Self::Osu(calculator) => Self::Osu(calculator.with_mods(mods)?),
+            Self::Taiko(calculator) => Self::Taiko(calculator.with_mods(mods)?),
+            Self::Catch(calculator) => Self::Catch(calculator.with_mods(mods)?),
+            Self::Mania(calculator) => Self::Mania(calculator.with_mods(mods)?),
+        };
+
+        Ok(calculator)
+    }
+
+    /// Calculates [`AnyDifficultyAttributes`] of the calculator's ruleset
+    ///
+    /// # Errors
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    pub fn calculate(&self) -> Result<AnyDifficultyAttributes, OsuError> {
+        let attributes = match self {
+            Self::Osu(calculator) => calculator.calculate()?.into(),
+            Self::Taiko(calculator) => calculator.calculate()?.into(),
+            Self::Catch(calculator) => calculator.calculate()?.into(),
+            Self::Mania(calculator) => calculator.calculate()?.into(),
+        };
+
+        Ok(attributes)
+    }
+
+    /// Calculates [`AnyDifficultyAttributes`] for `mods`, ignoring the calculator's own mods
+    ///
+    /// # Errors
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    pub fn calculate_with_mods(
+        &self,
+        mods: &GameMods,
+    ) -> Result<AnyDifficultyAttributes, OsuError> {
+        let attributes = match self {
+            Self::Osu(calculator) => calculator.calculate_with_mods(mods)?.into(),
+            Self::Taiko(calculator) => calculator.calculate_with_mods(mods)?.into(),
+            Self::Catch(calculator) => calculator.calculate_with_mods(mods)?.into(),
+            Self::Mania(calculator) => calculator.calculate_with_mods(mods)?.into(),
+        };
+
+        Ok(attributes)
+    }
+
+    /// Calculates [`AnyDifficultyAttributes`] for every mod combination in `mods`,
+    /// see [`DifficultyCalculator::calculate_batch`]
+    ///
+    /// # Errors
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    pub fn calculate_batch(
+        &self,
+        mods: &[GameMods],
+    ) -> Result<Vec<AnyDifficultyAttributes>, OsuError> {
+        fn wrap<A: Into<AnyDifficultyAttributes>>(
+            attributes: Vec<A>,
+        ) -> Vec<AnyDifficultyAttributes> {
+            attributes.into_iter().map(Into::into).collect()
+        }
+
+        let attributes = match self {
+            Self::Osu(calculator) => wrap(calculator.calculate_batch(mods)?),
+            Self::Taiko(calculator) => wrap(calculator.calculate_batch(mods)?),
+            Self::Catch(calculator) => wrap(calculator.calculate_batch(mods)?),
+            Self::Mania(calculator) => wrap(calculator.calculate_batch(mods)?),
+        };
+
+        Ok(attributes)
+    }
+}
+
+/// Difficulty attributes of any ruleset
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnyDifficultyAttributes {
+    Osu(OsuDifficultyAttributes),
+    Taiko(TaikoDifficultyAttributes),
+    Catch(CatchDifficultyAttributes),
+    Mania(ManiaDifficultyAttributes),
+}
+
+impl AnyDifficultyAttributes {
+    pub fn ruleset(&self) -> RulesetKind {
+        match self {
+            Self::Osu(_) => RulesetKind::Osu,
+            Self::Taiko(_) => RulesetKind::Taiko,
+            Self::Catch(_) => RulesetKind::Catch,
+            Self::Mania(_) => RulesetKind::Mania,
+        }
+    }
+
+    pub fn star_rating(&self) -> f64 {
+        match self {
+            Self::Osu(attributes) => attributes.star_rating,
+            Self::Taiko(attributes) => attributes.star_rating,
+            Self::Catch(attributes) => attributes.star_rating,
+            Self::Mania(attributes) => attributes.star_rating,
+        }
+    }
+
+    pub fn max_combo(&self) -> i32 {
+        match self {
+            Self::Osu(attributes) => attributes.max_combo,
+            Self::Taiko(attributes) => attributes.max_combo,
+            Self::Catch(attributes) => attributes.max_combo,
+            Self::Mania(attributes) => attributes.max_combo,
+        }
+    }
+}
+
+macro_rules! impl_any_conversions {
+    ( $( $variant:ident: $attributes:ident, )* ) => {
+        $(
+            impl From<$attributes> for AnyDifficultyAttributes {
+                fn from(attributes: $attributes) -> Self {
+                    Self::$variant(attributes)
+                }
+            }
+
+            impl TryFrom<AnyDifficultyAttributes> for $attributes {
+                type Error = RulesetMismatch;
+
+                fn try_from(attributes: AnyDifficultyAttributes) -> Result<Self, Self::Error> {
+                    match attributes {
+                        AnyDifficultyAttributes::$variant(attributes) => Ok(attributes),
+                        other => Err(RulesetMismatch {
+                            expected: RulesetKind::$variant,
+                            actual: other.ruleset(),
+                        }),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_any_conversions! {
+    Osu: OsuDifficultyAttributes,
+    Taiko: TaikoDifficultyAttributes,
+    Catch: CatchDifficultyAttributes,
+    Mania: ManiaDifficultyAttributes,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnyDifficultyAttributes, AnyDifficultyCalculator};
+    use crate::{
+        beatmap::Beatmap,
+        difficulty::{
+            DifficultyCalculator,
+            catch::CatchDifficultyAttributes,
+            mania::ManiaDifficultyAttributes,
+            osu::{OsuDifficultyAttributes, OsuDifficultyCalculator},
+            taiko::TaikoDifficultyCalculator,
+        },
+        ruleset::{Ruleset, RulesetKind},
+        utils::initialize_path,
+    };
+
+    #[test]
+    fn test_any_calculator_matches_specific() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+
+        let any = AnyDifficultyCalculator::from_kind(RulesetKind::Osu, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let specific = OsuDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+
+        assert_eq!(any.ruleset(), RulesetKind::Osu);
+        assert_eq!(any.star_rating(), specific.star_rating);
+        assert_eq!(OsuDifficultyAttributes::try_from(any).unwrap(), specific);
+    }
+
+    #[test]
+    fn test_any_calculator_with_mods() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+
+        let calculator = AnyDifficultyCalculator::from_kind(RulesetKind::Taiko, &beatmap)
+            .unwrap()
+            .with_mods(64)
+            .unwrap();
+        assert_eq!(calculator.ruleset(), RulesetKind::Taiko);
+        assert!(calculator.mods().contains("DT"));
+
+        let ruleset = Ruleset::new(RulesetKind::Taiko).unwrap();
+        let specific = TaikoDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .with_mods(64)
+            .unwrap()
+            .calculate()
+            .unwrap();
+
+        assert_eq!(calculator.calculate().unwrap(), specific.into());
+    }
+
+    #[test]
+    fn test_any_attributes_conversions() {
+        let attributes: AnyDifficultyAttributes = ManiaDifficultyAttributes {
+            star_rating: 4.5,
+            max_combo: 1200,
+        }
+        .into();
+
+        assert_eq!(attributes.ruleset(), RulesetKind::Mania);
+        assert_eq!(attributes.star_rating(), 4.5);
+        assert_eq!(attributes.max_combo(), 1200);
+
+        let mismatch = CatchDifficultyAttributes::try_from(attributes).unwrap_err();
+        assert_eq!(mismatch.expected, RulesetKind::Catch);
+        assert_eq!(mismatch.actual, RulesetKind::Mania);
+    }
+}
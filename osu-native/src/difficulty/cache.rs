@@ -0,0 +1,450 @@
+//! Difficulty attribute cache with an in-memory LRU and an optional on-disk store
+//!
+//! Entries are keyed by beatmap checksum, ruleset and the mods that affect
+//! difficulty, so e.g. HD and NC share the entry of DT for osu!mania. The disk
+//! store is wiped when it was written for a different [`REVISION`].
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{
+//! #     beatmap::Beatmap,
+//! #     difficulty::cache::DifficultyCache,
+//! #     mods::IntoGameMods,
+//! #     ruleset::RulesetKind,
+//! # };
+//! # let beatmap = Beatmap::from_path("../../standard.osu")?;
+//! let cache = DifficultyCache::new(1024).with_disk("difficulty-cache")?;
+//! let mods = 64.into_mods()?;
+//!
+//! // Calculated once, then served from memory or disk
+//! let attributes = cache.calculate(&beatmap, RulesetKind::Osu, &mods)?;
+//! println!("{:.2}*", attributes.star_rating());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::{Beatmap, checksum::md5_hex},
+    difficulty::any::{AnyDifficultyAttributes, AnyDifficultyCalculator},
+    error::OsuError,
+    mods::GameMods,
+    ruleset::RulesetKind,
+};
+
+/// The osu-native and lazer revision cached attributes were calculated with
+///
+/// Must be bumped together with the compliance section of COVERAGE.md
+pub const REVISION: &str = "lazer-2025.1029.0+osu-native-37dbd06";
+
+const REVISION_FILE: &str = "REVISION";
+
+#[derive(Debug, ThisError)]
+#[error("Invalid MD5 checksum {0:?}")]
+pub struct InvalidChecksum(String);
+
+/// Identifies the attributes of a beatmap played in a ruleset with some mods
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    checksum: String,
+    ruleset: RulesetKind,
+    mods: String,
+}
+
+impl CacheKey {
+    /// Creates a key from a beatmap's MD5 checksum
+    ///
+    /// Mods that don't affect difficulty in `ruleset` are ignored
+    ///
+    /// # Errors
+    /// Returns an [`InvalidChecksum`] if `checksum` isn't 32 hex digits, since it
+    /// names the key's directory in the disk store
+    pub fn new(
+        checksum: &str,
+        ruleset: RulesetKind,
+        mods: &GameMods,
+    ) -> Result<Self, InvalidChecksum> {
+        if !is_checksum(checksum) {
+            return Err(InvalidChecksum(checksum.to_owned()));
+        }
+
+        Ok(Self::new_unchecked(checksum, ruleset, mods))
+    }
+
+    fn new_unchecked(checksum: &str, ruleset: RulesetKind, mods: &GameMods) -> Self {
+        Self {
+            checksum: checksum.to_ascii_lowercase(),
+            ruleset,
+            mods: canonical_mods(mods, ruleset),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        format!(
+            "{}-{}.json",
+            i32::from(self.ruleset),
+            md5_hex(self.mods.as_bytes())
+        )
+    }
+}
+
+/// Deterministic text form of the difficulty-affecting mods, e.g. `DT(speed_change=Number(1.3))HD`
+fn canonical_mods(mods: &GameMods, ruleset: RulesetKind) -> String {
+    let mut canonical = String::new();
+
+    for gamemod in mods.difficulty_equivalent(Some(ruleset)).0 {
+        canonical.push_str(gamemod.acronym.as_str());
+
+        if gamemod.settings.is_empty() {
+            continue;
+        }
+
+        let mut settings: Vec<_> = gamemod.settings.iter().collect();
+        settings.sort_by_key(|(key, _)| *key);
+
+        let settings: Vec<_> = settings
+            .into_iter()
+            .map(|(key, value)| format!("{key}={value:?}"))
+            .collect();
+
+        canonical.push('(');
+        canonical.push_str(&settings.join(","));
+        canonical.push(')');
+    }
+
+    canonical
+}
+
+/// Least recently used entries, evicted once `capacity` is exceeded
+struct Lru {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<CacheKey, (AnyDifficultyAttributes, u64)>,
+    order: BTreeMap<u64, CacheKey>,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    fn touch(&mut self, key: &CacheKey) -> Option<AnyDifficultyAttributes> {
+        let (attributes, used) = self.entries.get_mut(key)?;
+
+        self.order.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.order.insert(self.tick, key.clone());
+
+        Some(attributes.clone())
+    }
+
+    fn insert(&mut self, key: CacheKey, attributes: AnyDifficultyAttributes) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.tick += 1;
+
+        if let Some((_, used)) = self.entries.insert(key.clone(), (attributes, self.tick)) {
+            self.order.remove(&used);
+        }
+
+        self.order.insert(self.tick, key);
+
+        while self.entries.len() > self.capacity {
+            let Some((_, key)) = self.order.pop_first() else {
+                break;
+            };
+
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// File-per-key store, one directory per beatmap checksum
+struct DiskStore {
+    dir: PathBuf,
+}
+
+impl DiskStore {
+    fn open(dir: PathBuf, revision: &str) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+
+        let revision_file = dir.join(REVISION_FILE);
+
+        let stored = match fs::read_to_string(&revision_file) {
+            Ok(stored) => Some(stored),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+
+        if stored.as_deref().map(str::trim) != Some(revision) {
+            clear_entries(&dir)?;
+            fs::write(&revision_file, revision)?;
+        }
+
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(&key.checksum).join(key.file_name())
+    }
+
+    /// Unreadable or corrupt entries count as misses
+    fn get(&self, key: &CacheKey) -> Option<AnyDifficultyAttributes> {
+        let bytes = fs::read(self.path(key)).ok()?;
+        let attributes: AnyDifficultyAttributes = serde_json::from_slice(&bytes).ok()?;
+
+        (attributes.ruleset() == key.ruleset).then_some(attributes)
+    }
+
+    fn insert(&self, key: &CacheKey, attributes: &AnyDifficultyAttributes) -> io::Result<()> {
+        let path = self.path(key);
+        let tmp = path.with_extension("tmp");
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&tmp, serde_json::to_vec(attributes)?)?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// Removes the checksum directories of a store, leaving unrelated files alone
+fn clear_entries(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        if is_checksum(&name) && entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn is_checksum(name: &str) -> bool {
+    name.len() == 32 && name.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Cache in front of the difficulty calculators, safe to share between threads
+pub struct DifficultyCache {
+    memory: Mutex<Lru>,
+    disk: Option<DiskStore>,
+}
+
+impl DifficultyCache {
+    /// Creates a memory-only cache holding up to `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            memory: Mutex::new(Lru::new(capacity)),
+            disk: None,
+        }
+    }
+
+    /// Persists entries in `dir`, see [`with_disk_revision`](Self::with_disk_revision)
+    ///
+    /// # Errors
+    /// Returns an [`io::Error`] if the directory can't be created or cleared
+    pub fn with_disk(self, dir: impl Into<PathBuf>) -> io::Result<Self> {
+        self.with_disk_revision(dir, REVISION)
+    }
+
+    /// Persists entries in `dir`, removing existing entries if they were stored
+    /// for another `revision`
+    ///
+    /// # Errors
+    /// Returns an [`io::Error`] if the directory can't be created or cleared
+    pub fn with_disk_revision(
+        mut self,
+        dir: impl Into<PathBuf>,
+        revision: &str,
+    ) -> io::Result<Self> {
+        self.disk = Some(DiskStore::open(dir.into(), revision)?);
+
+        Ok(self)
+    }
+
+    /// Cached attributes for `key`, checking memory before disk
+    pub fn get(&self, key: &CacheKey) -> Option<AnyDifficultyAttributes> {
+        if let Some(attributes) = self.lock().touch(key) {
+            return Some(attributes);
+        }
+
+        let attributes = self.disk.as_ref()?.get(key)?;
+        self.lock().insert(key.clone(), attributes.clone());
+
+        Some(attributes)
+    }
+
+    /// Stores attributes in memory and, if enabled, on disk
+    ///
+    /// # Errors
+    /// Returns an [`io::Error`] if the disk entry can't be written
+    pub fn insert(&self, key: CacheKey, attributes: AnyDifficultyAttributes) -> io::Result<()> {
+        if let Some(ref disk) = self.disk {
+            disk.insert(&key, &attributes)?;
+        }
+
+        self.lock().insert(key, attributes);
+
+        Ok(())
+    }
+
+    /// Returns cached attributes or calculates and caches them
+    ///
+    /// The calculator is only created on a miss. Failing to write the disk entry
+    /// doesn't fail the calculation, the attributes are still cached in memory
+    ///
+    /// # Errors
+    /// Returns an [`OsuError`] if the calculation fails
+    pub fn calculate(
+        &self,
+        beatmap: &Beatmap,
+        ruleset: RulesetKind,
+        mods: &GameMods,
+    ) -> Result<AnyDifficultyAttributes, OsuError> {
+        // Always a valid MD5 hex digest
+        let key = CacheKey::new_unchecked(beatmap.checksum(), ruleset, mods);
+
+        if let Some(attributes) = self.get(&key) {
+            return Ok(attributes);
+        }
+
+        let attributes =
+            AnyDifficultyCalculator::from_kind(ruleset, beatmap)?.calculate_with_mods(mods)?;
+
+        if self.insert(key.clone(), attributes.clone()).is_err() {
+            self.lock().insert(key, attributes.clone());
+        }
+
+        Ok(attributes)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru> {
+        // The LRU stays consistent even if a holder panicked
+        self.memory.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, str::FromStr};
+
+    use rosu_mods::{Acronym, GameModSimple};
+
+    use super::{CacheKey, DifficultyCache, InvalidChecksum, REVISION_FILE};
+    use crate::{
+        difficulty::{any::AnyDifficultyAttributes, mania::ManiaDifficultyAttributes},
+        mods::GameMods,
+        ruleset::RulesetKind,
+        utils::unique_temp_dir,
+    };
+
+    const CHECKSUM: &str = "f52cf288046c2f07335a280096a5b4fb";
+
+    fn mods(acronyms: &[&str]) -> GameMods {
+        GameMods(
+            acronyms
+                .iter()
+                .map(|acronym| GameModSimple {
+                    acronym: Acronym::from_str(acronym).unwrap(),
+                    settings: Default::default(),
+                })
+                .collect(),
+        )
+    }
+
+    fn attributes(star_rating: f64) -> AnyDifficultyAttributes {
+        ManiaDifficultyAttributes {
+            star_rating,
+            max_combo: 1000,
+        }
+        .into()
+    }
+
+    fn key(acronyms: &[&str]) -> CacheKey {
+        CacheKey::new(CHECKSUM, RulesetKind::Mania, &mods(acronyms)).unwrap()
+    }
+
+    #[test]
+    fn test_key_ignores_irrelevant_mods() {
+        assert_eq!(key(&["HD", "NC"]), key(&["DT"]));
+        assert_eq!(key(&["NF"]), key(&[]));
+        assert_ne!(key(&["DT"]), key(&["HT"]));
+        assert_ne!(
+            CacheKey::new(CHECKSUM, RulesetKind::Osu, &mods(&["HD"])).unwrap(),
+            CacheKey::new(CHECKSUM, RulesetKind::Osu, &mods(&[])).unwrap()
+        );
+        assert_eq!(
+            CacheKey::new(
+                &CHECKSUM.to_ascii_uppercase(),
+                RulesetKind::Mania,
+                &mods(&[])
+            )
+            .unwrap(),
+            key(&[])
+        );
+    }
+
+    #[test]
+    fn test_key_rejects_invalid_checksum() {
+        for checksum in ["../../x", "/tmp", "", "f52cf288046c2f07335a280096a5b4f/"] {
+            assert!(matches!(
+                CacheKey::new(checksum, RulesetKind::Osu, &mods(&[])),
+                Err(InvalidChecksum(invalid)) if invalid == checksum
+            ));
+        }
+    }
+
+    #[test]
+    fn test_memory_eviction() {
+        let cache = DifficultyCache::new(2);
+
+        cache.insert(key(&[]), attributes(1.0)).unwrap();
+        cache.insert(key(&["DT"]), attributes(2.0)).unwrap();
+
+        // Using NM makes DT the least recently used entry
+        assert_eq!(cache.get(&key(&[])), Some(attributes(1.0)));
+        cache.insert(key(&["HT"]), attributes(0.5)).unwrap();
+
+        assert_eq!(cache.get(&key(&["DT"])), None);
+        assert_eq!(cache.get(&key(&[])), Some(attributes(1.0)));
+        assert_eq!(cache.get(&key(&["HT"])), Some(attributes(0.5)));
+    }
+
+    #[test]
+    fn test_disk_persistence_and_revision() {
+        let dir = unique_temp_dir("osu-native-difficulty-cache");
+
+        let cache = DifficultyCache::new(0).with_disk(&dir).unwrap();
+        cache.insert(key(&["DT"]), attributes(2.0)).unwrap();
+
+        let reopened = DifficultyCache::new(0).with_disk(&dir).unwrap();
+        assert_eq!(reopened.get(&key(&["NC"])), Some(attributes(2.0)));
+
+        let updated = DifficultyCache::new(0)
+            .with_disk_revision(&dir, "next")
+            .unwrap();
+        assert_eq!(updated.get(&key(&["DT"])), None);
+        assert_eq!(fs::read_to_string(dir.join(REVISION_FILE)).unwrap(), "next");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
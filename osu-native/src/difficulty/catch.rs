@@ -1,22 +1,23 @@
 use std::mem::MaybeUninit;
 
 use libosu_native_sys::{
-    CatchDifficultyCalculator_CalculateMods, CatchDifficultyCalculator_Create,
-    CatchDifficultyCalculator_Destroy, ErrorCode, NativeCatchDifficultyAttributes,
+    CatchDifficultyCalculator_Calculate, CatchDifficultyCalculator_CalculateMods,
+    CatchDifficultyCalculator_Create, CatchDifficultyCalculator_Destroy, ErrorCode,
+    NativeCatchDifficultyAttributes,
 };
 
 use crate::{
     beatmap::Beatmap,
     error::OsuError,
     mods::{
-        GameMods, GameModsError, IntoGameMods,
+        GameMods, IntoGameMods,
         native::{Mod, ModCollection},
     },
     ruleset::Ruleset,
     utils::HasNative,
 };
 
-use super::DifficultyCalculator;
+use super::{DifficultyCalculator, calculate_batch};
 
 #[derive(PartialEq)]
 pub struct CatchDifficultyCalculator {
@@ -25,6 +26,65 @@ pub struct CatchDifficultyCalculator {
     mods: GameMods,
 }
 
+impl CatchDifficultyCalculator {
+    /// Calculates through `CatchDifficultyCalculator_Calculate`, skipping the mod collection setup
+    fn calculate_without_mods(&self) -> Result<CatchDifficultyAttributes, OsuError> {
+        let mut attributes = MaybeUninit::uninit();
+
+        let code =
+            unsafe { CatchDifficultyCalculator_Calculate(self.handle, attributes.as_mut_ptr()) };
+
+        if code != ErrorCode::Success {
+            return Err(code.into());
+        }
+
+        let native = unsafe { attributes.assume_init() };
+
+        Ok(native.into())
+    }
+
+    fn calculate_with_collection(
+        &self,
+        game_mods: &GameMods,
+    ) -> Result<CatchDifficultyAttributes, OsuError> {
+        let mods = ModCollection::new()?;
+
+        let mods_vec = game_mods
+            .0
+            .iter()
+            .map(|gamemod| {
+                let m = Mod::new(gamemod.acronym.as_str())?;
+                m.apply_settings(&gamemod.settings)?;
+
+                Ok(m)
+            })
+            .collect::<Result<Vec<_>, OsuError>>()?;
+
+        for gamemod in mods_vec.iter() {
+            mods.add(gamemod)?;
+        }
+
+        let mut attributes = MaybeUninit::uninit();
+
+        let code = unsafe {
+            CatchDifficultyCalculator_CalculateMods(
+                self.handle,
+                self.ruleset.handle(),
+                mods.handle(),
+                attributes.as_mut_ptr(),
+            )
+        };
+
+        if code != ErrorCode::Success {
+            return Err(code.into());
+        }
+
+        let native = unsafe { attributes.assume_init() };
+
+        Ok(native.into())
+    }
+}
+
 impl Drop for CatchDifficultyCalculator {
     fn drop(&mut self) {
         unsafe { CatchDifficultyCalculator_Destroy(self.handle) };
@@ -71,11 +131,11 @@ impl DifficultyCalculator for CatchDifficultyCalculator {
         })
     }
 
-    fn mods(&self) -> GameMods {
-        self.mods.clone()
+    fn mods(&self) -> &GameMods {
+        &self.mods
     }
 
-    fn with_mods(mut self, mods: impl IntoGameMods) -> Result<Self, GameModsError> {
+    fn with_mods(mut self, mods: impl IntoGameMods) -> Result<Self, OsuError> {
         self.mods = mods.into_mods()?;
 
         Ok(self)
@@ -101,42 +161,38 @@ impl DifficultyCalculator for CatchDifficultyCalculator {
     ///
     /// Returns [`OsuError::NativeError`] if osu-native returns an error
     fn calculate(&self) -> Result<Self::DifficultyAttributes, OsuError> {
-        let mods = ModCollection::new()?;
-
-        let mods_vec = self
-            .mods
-            .0
-            .iter()
-            .map(|gamemod| {
-                let m = Mod::new(gamemod.acronym.as_str())?;
-                m.apply_settings(&gamemod.settings)?;
-
-                Ok(m)
-            })
-            .collect::<Result<Vec<_>, OsuError>>()?;
-
-        for gamemod in mods_vec.iter() {
-            mods.add(gamemod)?;
-        }
-
-        let mut attributes = MaybeUninit::uninit();
-
-        let code = unsafe {
-            CatchDifficultyCalculator_CalculateMods(
-                self.handle,
-                self.ruleset.handle(),
-                mods.handle(),
-                attributes.as_mut_ptr(),
-            )
-        };
+        self.calculate_with_mods(&self.mods)
+    }
 
-        if code != ErrorCode::Success {
-            return Err(code.into());
+    /// Calculates [`CatchDifficultyAttributes`] for `mods`, ignoring the calculator's own mods
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    fn calculate_with_mods(&self, mods: &GameMods) -> Result<Self::DifficultyAttributes, OsuError> {
+        if mods.is_empty() {
+            self.calculate_without_mods()
+        } else {
+            self.calculate_with_collection(mods)
         }
+    }
 
-        let native = unsafe { attributes.assume_init() };
-
-        Ok(native.into())
+    /// Calculates [`CatchDifficultyAttributes`] for every mod combination in `mods`,
+    /// see [`DifficultyCalculator::calculate_batch`]
+    ///
+    /// Also groups combinations that only differ by HD and FL, which don't affect
+    /// catch difficulty
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    fn calculate_batch(
+        &self,
+        mods: &[GameMods],
+    ) -> Result<Vec<Self::DifficultyAttributes>, OsuError> {
+        calculate_batch(Some(self.ruleset.kind), mods, |mods| {
+            self.calculate_with_mods(mods)
+        })
     }
 }
 
@@ -157,7 +213,8 @@ impl From<&CatchDifficultyAttributes> for NativeCatchDifficultyAttributes {
         }
     }
 }
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchDifficultyAttributes {
     pub star_rating: f64,
     pub max_combo: i32,
@@ -177,6 +234,7 @@ mod tests {
         beatmap::Beatmap,
         difficulty::{DifficultyCalculator, catch::CatchDifficultyCalculator},
         generate_diffcalc_field_tests,
+        mods::GameMods,
         ruleset::{Ruleset, RulesetKind},
         utils::initialize_path,
     };
@@ -208,4 +266,18 @@ mod tests {
         assert!(attributes_with_mods.star_rating > attributes.star_rating);
         assert!(attributes_with_mods.max_combo == attributes.max_combo);
     }
+
+    #[test]
+    fn test_calculate_without_mods_matches_collection() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Catch).unwrap();
+        let calculator = CatchDifficultyCalculator::new(ruleset, &beatmap).unwrap();
+
+        assert_eq!(
+            calculator.calculate_without_mods().unwrap(),
+            calculator
+                .calculate_with_collection(&GameMods::default())
+                .unwrap()
+        );
+    }
 }
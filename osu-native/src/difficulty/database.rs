@@ -0,0 +1,343 @@
+//! Conversion from and to the `(attrib_id, value)` rows of osu-web's
+//! `osu_beatmap_difficulty_attribs` table, using lazer's attribute ids
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::difficulty::{database::BeatmapOnlineInfo, osu::OsuDifficultyAttributes};
+//! // Rows of `osu_beatmap_difficulty_attribs` for a beatmap and mod combination
+//! let rows = [(1, 2.8), (3, 2.5), (9, 719.0), (11, 5.6), (19, 0.98)];
+//! let beatmap = BeatmapOnlineInfo {
+//!     drain_rate: 5.0,
+//!     circle_count: 300,
+//!     slider_count: 200,
+//!     spinner_count: 2,
+//! };
+//!
+//! let attributes = OsuDifficultyAttributes::from_database_attributes(&rows, &beatmap)?;
+//! assert_eq!(attributes.max_combo, 719);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use thiserror::Error as ThisError;
+
+use crate::difficulty::{
+    catch::CatchDifficultyAttributes, mania::ManiaDifficultyAttributes,
+    osu::OsuDifficultyAttributes, taiko::TaikoDifficultyAttributes,
+};
+
+pub const ATTRIB_ID_AIM: u32 = 1;
+pub const ATTRIB_ID_SPEED: u32 = 3;
+pub const ATTRIB_ID_MAX_COMBO: u32 = 9;
+pub const ATTRIB_ID_DIFFICULTY: u32 = 11;
+pub const ATTRIB_ID_FLASHLIGHT: u32 = 17;
+pub const ATTRIB_ID_SLIDER_FACTOR: u32 = 19;
+pub const ATTRIB_ID_SPEED_NOTE_COUNT: u32 = 21;
+pub const ATTRIB_ID_SPEED_DIFFICULT_STRAIN_COUNT: u32 = 23;
+pub const ATTRIB_ID_AIM_DIFFICULT_STRAIN_COUNT: u32 = 25;
+pub const ATTRIB_ID_MONO_STAMINA_FACTOR: u32 = 29;
+pub const ATTRIB_ID_AIM_DIFFICULT_SLIDER_COUNT: u32 = 31;
+pub const ATTRIB_ID_AIM_TOP_WEIGHTED_SLIDER_FACTOR: u32 = 33;
+pub const ATTRIB_ID_SPEED_TOP_WEIGHTED_SLIDER_FACTOR: u32 = 35;
+pub const ATTRIB_ID_NESTED_SCORE_PER_OBJECT: u32 = 37;
+pub const ATTRIB_ID_LEGACY_SCORE_BASE_MULTIPLIER: u32 = 39;
+pub const ATTRIB_ID_MAXIMUM_LEGACY_COMBO_SCORE: u32 = 41;
+
+#[derive(Debug, ThisError)]
+pub enum DatabaseAttributesError {
+    #[error("Missing required attribute {0}")]
+    Missing(u32),
+}
+
+/// Beatmap values lazer reads from the `osu_beatmaps` row instead of the
+/// attributes table
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BeatmapOnlineInfo {
+    pub drain_rate: f64,
+    pub circle_count: i32,
+    pub slider_count: i32,
+    pub spinner_count: i32,
+}
+
+/// Looks up attribute values by id, the last row winning on duplicates
+struct Rows<'a>(&'a [(u32, f64)]);
+
+impl Rows<'_> {
+    fn get(&self, id: u32) -> Option<f64> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(attrib_id, _)| *attrib_id == id)
+            .map(|(_, value)| *value)
+    }
+
+    fn required(&self, id: u32) -> Result<f64, DatabaseAttributesError> {
+        self.get(id).ok_or(DatabaseAttributesError::Missing(id))
+    }
+
+    fn or_zero(&self, id: u32) -> f64 {
+        self.get(id).unwrap_or(0.0)
+    }
+
+    fn max_combo(&self) -> Result<i32, DatabaseAttributesError> {
+        self.required(ATTRIB_ID_MAX_COMBO)
+            .map(|max_combo| max_combo.round() as i32)
+    }
+}
+
+impl OsuDifficultyAttributes {
+    /// Attribute rows as lazer writes them, flashlight only if it's non-zero
+    pub fn to_database_attributes(&self) -> Vec<(u32, f64)> {
+        let mut attributes = vec![
+            (ATTRIB_ID_MAX_COMBO, f64::from(self.max_combo)),
+            (ATTRIB_ID_AIM, self.aim_difficulty),
+            (ATTRIB_ID_SPEED, self.speed_difficulty),
+            (ATTRIB_ID_DIFFICULTY, self.star_rating),
+        ];
+
+        if self.flashlight_difficulty > 0.0 {
+            attributes.push((ATTRIB_ID_FLASHLIGHT, self.flashlight_difficulty));
+        }
+
+        attributes.extend([
+            (ATTRIB_ID_SLIDER_FACTOR, self.slider_factor),
+            (
+                ATTRIB_ID_AIM_DIFFICULT_STRAIN_COUNT,
+                self.aim_difficult_strain_count,
+            ),
+            (
+                ATTRIB_ID_SPEED_DIFFICULT_STRAIN_COUNT,
+                self.speed_difficult_strain_count,
+            ),
+            (ATTRIB_ID_SPEED_NOTE_COUNT, self.speed_note_count),
+            (
+                ATTRIB_ID_AIM_DIFFICULT_SLIDER_COUNT,
+                self.aim_difficulty_slider_count,
+            ),
+            (
+                ATTRIB_ID_AIM_TOP_WEIGHTED_SLIDER_FACTOR,
+                self.aim_top_weighted_slider_factor,
+            ),
+            (
+                ATTRIB_ID_SPEED_TOP_WEIGHTED_SLIDER_FACTOR,
+                self.speed_top_weighted_slider_factor,
+            ),
+            (
+                ATTRIB_ID_NESTED_SCORE_PER_OBJECT,
+                self.nested_score_per_object,
+            ),
+            (
+                ATTRIB_ID_LEGACY_SCORE_BASE_MULTIPLIER,
+                self.legacy_score_base_multiplier,
+            ),
+            (
+                ATTRIB_ID_MAXIMUM_LEGACY_COMBO_SCORE,
+                self.maximum_legacy_combo_score,
+            ),
+        ]);
+
+        attributes
+    }
+
+    /// Reconstructs attributes from database rows and the beatmap's online info
+    ///
+    /// Attributes added to lazer after a dump was made default to 0
+    ///
+    /// # Errors
+    /// Returns [`DatabaseAttributesError::Missing`] if the max combo or star rating
+    /// rows are missing
+    pub fn from_database_attributes(
+        attributes: &[(u32, f64)],
+        beatmap: &BeatmapOnlineInfo,
+    ) -> Result<Self, DatabaseAttributesError> {
+        let rows = Rows(attributes);
+
+        Ok(Self {
+            star_rating: rows.required(ATTRIB_ID_DIFFICULTY)?,
+            max_combo: rows.max_combo()?,
+            aim_difficulty: rows.or_zero(ATTRIB_ID_AIM),
+            aim_difficulty_slider_count: rows.or_zero(ATTRIB_ID_AIM_DIFFICULT_SLIDER_COUNT),
+            speed_difficulty: rows.or_zero(ATTRIB_ID_SPEED),
+            speed_note_count: rows.or_zero(ATTRIB_ID_SPEED_NOTE_COUNT),
+            flashlight_difficulty: rows.or_zero(ATTRIB_ID_FLASHLIGHT),
+            slider_factor: rows.or_zero(ATTRIB_ID_SLIDER_FACTOR),
+            aim_top_weighted_slider_factor: rows.or_zero(ATTRIB_ID_AIM_TOP_WEIGHTED_SLIDER_FACTOR),
+            speed_top_weighted_slider_factor: rows
+                .or_zero(ATTRIB_ID_SPEED_TOP_WEIGHTED_SLIDER_FACTOR),
+            aim_difficult_strain_count: rows.or_zero(ATTRIB_ID_AIM_DIFFICULT_STRAIN_COUNT),
+            speed_difficult_strain_count: rows.or_zero(ATTRIB_ID_SPEED_DIFFICULT_STRAIN_COUNT),
+            nested_score_per_object: rows.or_zero(ATTRIB_ID_NESTED_SCORE_PER_OBJECT),
+            legacy_score_base_multiplier: rows.or_zero(ATTRIB_ID_LEGACY_SCORE_BASE_MULTIPLIER),
+            maximum_legacy_combo_score: rows.or_zero(ATTRIB_ID_MAXIMUM_LEGACY_COMBO_SCORE),
+            drain_rate: beatmap.drain_rate,
+            hit_circle_count: beatmap.circle_count,
+            slider_count: beatmap.slider_count,
+            spinner_count: beatmap.spinner_count,
+        })
+    }
+}
+
+impl TaikoDifficultyAttributes {
+    /// Attribute rows as lazer writes them
+    pub fn to_database_attributes(&self) -> Vec<(u32, f64)> {
+        vec![
+            (ATTRIB_ID_MAX_COMBO, f64::from(self.max_combo)),
+            (ATTRIB_ID_DIFFICULTY, self.star_rating),
+            (ATTRIB_ID_MONO_STAMINA_FACTOR, self.mono_stamina_factor),
+        ]
+    }
+
+    /// Reconstructs attributes from database rows
+    ///
+    /// Skill difficulties aren't stored in the database and are left at 0
+    ///
+    /// # Errors
+    /// Returns [`DatabaseAttributesError::Missing`] if the max combo or star rating
+    /// rows are missing
+    pub fn from_database_attributes(
+        attributes: &[(u32, f64)],
+    ) -> Result<Self, DatabaseAttributesError> {
+        let rows = Rows(attributes);
+
+        Ok(Self {
+            star_rating: rows.required(ATTRIB_ID_DIFFICULTY)?,
+            max_combo: rows.max_combo()?,
+            mechanical_difficulty: 0.0,
+            rhythm_difficulty: 0.0,
+            reading_difficulty: 0.0,
+            colour_difficulty: 0.0,
+            stamina_difficulty: 0.0,
+            mono_stamina_factor: rows.or_zero(ATTRIB_ID_MONO_STAMINA_FACTOR),
+            consistency_factor: 0.0,
+            stamina_top_strains: 0.0,
+        })
+    }
+}
+
+macro_rules! impl_basic_database_attributes {
+    ( $( $attributes:ident ),* ) => {
+        $(
+            impl $attributes {
+                /// Attribute rows as lazer writes them
+                pub fn to_database_attributes(&self) -> Vec<(u32, f64)> {
+                    vec![
+                        (ATTRIB_ID_MAX_COMBO, f64::from(self.max_combo)),
+                        (ATTRIB_ID_DIFFICULTY, self.star_rating),
+                    ]
+                }
+
+                /// Reconstructs attributes from database rows
+                ///
+                /// # Errors
+                /// Returns [`DatabaseAttributesError::Missing`] if the max combo or
+                /// star rating rows are missing
+                pub fn from_database_attributes(
+                    attributes: &[(u32, f64)],
+                ) -> Result<Self, DatabaseAttributesError> {
+                    let rows = Rows(attributes);
+
+                    Ok(Self {
+                        star_rating: rows.required(ATTRIB_ID_DIFFICULTY)?,
+                        max_combo: rows.max_combo()?,
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_basic_database_attributes!(CatchDifficultyAttributes, ManiaDifficultyAttributes);
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ATTRIB_ID_DIFFICULTY, ATTRIB_ID_FLASHLIGHT, ATTRIB_ID_MAX_COMBO, BeatmapOnlineInfo,
+        DatabaseAttributesError,
+    };
+    use crate::{
+        beatmap::Beatmap,
+        difficulty::{
+            DifficultyCalculator,
+            mania::ManiaDifficultyAttributes,
+            osu::{OsuDifficultyAttributes, OsuDifficultyCalculator},
+        },
+        performance::{PerformanceCalculator, ScoreStatistics, osu::OsuPerformanceCalculator},
+        ruleset::{Ruleset, RulesetKind},
+        utils::initialize_path,
+    };
+
+    #[test]
+    fn test_basic_round_trip() {
+        let attributes = ManiaDifficultyAttributes {
+            star_rating: 4.5,
+            max_combo: 1200,
+        };
+
+        let rows = attributes.to_database_attributes();
+        assert_eq!(
+            rows,
+            [(ATTRIB_ID_MAX_COMBO, 1200.0), (ATTRIB_ID_DIFFICULTY, 4.5)]
+        );
+        assert_eq!(
+            ManiaDifficultyAttributes::from_database_attributes(&rows).unwrap(),
+            attributes
+        );
+    }
+
+    #[test]
+    fn test_missing_attributes() {
+        let beatmap = BeatmapOnlineInfo::default();
+
+        assert!(matches!(
+            OsuDifficultyAttributes::from_database_attributes(
+                &[(ATTRIB_ID_MAX_COMBO, 100.0)],
+                &beatmap
+            ),
+            Err(DatabaseAttributesError::Missing(ATTRIB_ID_DIFFICULTY))
+        ));
+
+        // Everything but star rating and max combo is optional
+        let attributes = OsuDifficultyAttributes::from_database_attributes(
+            &[(ATTRIB_ID_MAX_COMBO, 100.0), (ATTRIB_ID_DIFFICULTY, 3.0)],
+            &beatmap,
+        )
+        .unwrap();
+        assert_eq!(attributes.max_combo, 100);
+        assert_eq!(attributes.aim_difficulty, 0.0);
+    }
+
+    #[test]
+    fn test_osu_round_trip_into_performance() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let attributes = OsuDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+
+        let rows = attributes.to_database_attributes();
+        assert!(rows.iter().all(|(id, _)| *id != ATTRIB_ID_FLASHLIGHT));
+
+        let info = BeatmapOnlineInfo {
+            drain_rate: attributes.drain_rate,
+            circle_count: attributes.hit_circle_count,
+            slider_count: attributes.slider_count,
+            spinner_count: attributes.spinner_count,
+        };
+        let imported = OsuDifficultyAttributes::from_database_attributes(&rows, &info).unwrap();
+        assert_eq!(imported, attributes);
+
+        let score = ScoreStatistics {
+            max_combo: imported.max_combo,
+            count_great: imported.hit_circle_count + imported.slider_count + imported.spinner_count,
+            count_slider_tail_hit: imported.slider_count,
+            ..Default::default()
+        };
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let performance = OsuPerformanceCalculator::new()
+            .unwrap()
+            .calculate(&ruleset, &score, &beatmap, 0, &imported)
+            .unwrap();
+
+        assert!(performance.pp > 0.0);
+    }
+}
@@ -1,22 +1,23 @@
 use std::mem::MaybeUninit;
 
 use libosu_native_sys::{
-    ErrorCode, ManiaDifficultyCalculator_CalculateMods, ManiaDifficultyCalculator_Create,
-    ManiaDifficultyCalculator_Destroy, NativeManiaDifficultyAttributes,
+    ErrorCode, ManiaDifficultyCalculator_Calculate, ManiaDifficultyCalculator_CalculateMods,
+    ManiaDifficultyCalculator_Create, ManiaDifficultyCalculator_Destroy,
+    NativeManiaDifficultyAttributes,
 };
 
 use crate::{
     beatmap::Beatmap,
     error::OsuError,
     mods::{
-        GameMods, GameModsError, IntoGameMods,
+        GameMods, IntoGameMods,
         native::{Mod, ModCollection},
     },
     ruleset::Ruleset,
     utils::HasNative,
 };
 
-use super::DifficultyCalculator;
+use super::{DifficultyCalculator, calculate_batch};
 
 #[derive(PartialEq)]
 pub struct ManiaDifficultyCalculator {
@@ -25,6 +26,65 @@ pub struct ManiaDifficultyCalculator {
     mods: GameMods,
 }
 
+impl ManiaDifficultyCalculator {
+    /// Calculates through `ManiaDifficultyCalculator_Calculate`, skipping the mod collection setup
+    fn calculate_without_mods(&self) -> Result<ManiaDifficultyAttributes, OsuError> {
+        let mut attributes = MaybeUninit::uninit();
+
+        let code =
+            unsafe { ManiaDifficultyCalculator_Calculate(self.handle, attributes.as_mut_ptr()) };
+
+        if code != ErrorCode::Success {
+            return Err(code.into());
+        }
+
+        let native = unsafe { attributes.assume_init() };
+
+        Ok(native.into())
+    }
+
+    fn calculate_with_collection(
+        &self,
+        game_mods: &GameMods,
+    ) -> Result<ManiaDifficultyAttributes, OsuError> {
+        let mods = ModCollection::new()?;
+
+        let mods_vec = game_mods
+            .0
+            .iter()
+            .map(|gamemod| {
+                let m = Mod::new(gamemod.acronym.as_str())?;
+                m.apply_settings(&gamemod.settings)?;
+
+                Ok(m)
+            })
+            .collect::<Result<Vec<_>, OsuError>>()?;
+
+        for gamemod in mods_vec.iter() {
+            mods.add(gamemod)?;
+        }
+
+        let mut attributes = MaybeUninit::uninit();
+
+        let code = unsafe {
+            ManiaDifficultyCalculator_CalculateMods(
+                self.handle,
+                self.ruleset.handle(),
+                mods.handle(),
+                attributes.as_mut_ptr(),
+            )
+        };
+
+        if code != ErrorCode::Success {
+            return Err(code.into());
+        }
+
+        let native = unsafe { attributes.assume_init() };
+
+        Ok(native.into())
+    }
+}
+
 impl Drop for ManiaDifficultyCalculator {
     fn drop(&mut self) {
         unsafe { ManiaDifficultyCalculator_Destroy(self.handle) };
@@ -70,11 +130,11 @@ impl DifficultyCalculator for ManiaDifficultyCalculator {
         })
     }
 
-    fn mods(&self) -> GameMods {
-        self.mods.clone()
+    fn mods(&self) -> &GameMods {
+        &self.mods
     }
 
-    fn with_mods(mut self, mods: impl IntoGameMods) -> Result<Self, GameModsError> {
+    fn with_mods(mut self, mods: impl IntoGameMods) -> Result<Self, OsuError> {
         self.mods = mods.into_mods()?;
 
         Ok(self)
@@ -101,46 +161,43 @@ impl DifficultyCalculator for ManiaDifficultyCalculator {
     ///
     /// Returns [`OsuError::NativeError`] if osu-native returns an error
     fn calculate(&self) -> Result<Self::DifficultyAttributes, OsuError> {
-        let mods = ModCollection::new()?;
-
-        let mods_vec = self
-            .mods
-            .0
-            .iter()
-            .map(|gamemod| {
-                let m = Mod::new(gamemod.acronym.as_str())?;
-                m.apply_settings(&gamemod.settings)?;
-
-                Ok(m)
-            })
-            .collect::<Result<Vec<_>, OsuError>>()?;
-
-        for gamemod in mods_vec.iter() {
-            mods.add(gamemod)?;
-        }
-
-        let mut attributes = MaybeUninit::uninit();
-
-        let code = unsafe {
-            ManiaDifficultyCalculator_CalculateMods(
-                self.handle,
-                self.ruleset.handle(),
-                mods.handle(),
-                attributes.as_mut_ptr(),
-            )
-        };
+        self.calculate_with_mods(&self.mods)
+    }
 
-        if code != ErrorCode::Success {
-            return Err(code.into());
+    /// Calculates [`ManiaDifficultyAttributes`] for `mods`, ignoring the calculator's own mods
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    fn calculate_with_mods(&self, mods: &GameMods) -> Result<Self::DifficultyAttributes, OsuError> {
+        if mods.is_empty() {
+            self.calculate_without_mods()
+        } else {
+            self.calculate_with_collection(mods)
         }
+    }
 
-        let native = unsafe { attributes.assume_init() };
-
-        Ok(native.into())
+    /// Calculates [`ManiaDifficultyAttributes`] for every mod combination in `mods`,
+    /// see [`DifficultyCalculator::calculate_batch`]
+    ///
+    /// Also groups combinations that only differ by HD, FI and FL, which don't affect
+    /// mania difficulty
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    fn calculate_batch(
+        &self,
+        mods: &[GameMods],
+    ) -> Result<Vec<Self::DifficultyAttributes>, OsuError> {
+        calculate_batch(Some(self.ruleset.kind), mods, |mods| {
+            self.calculate_with_mods(mods)
+        })
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ManiaDifficultyAttributes {
     pub star_rating: f64,
     pub max_combo: i32,
@@ -177,6 +234,7 @@ mod tests {
         beatmap::Beatmap,
         difficulty::{DifficultyCalculator, mania::ManiaDifficultyCalculator},
         generate_diffcalc_field_tests,
+        mods::GameMods,
         ruleset::{Ruleset, RulesetKind},
         utils::initialize_path,
     };
@@ -207,4 +265,18 @@ mod tests {
         assert!(attributes_with_mods.star_rating > attributes.star_rating);
         assert!(attributes_with_mods.max_combo == attributes.max_combo);
     }
+
+    #[test]
+    fn test_calculate_without_mods_matches_collection() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Mania).unwrap();
+        let calculator = ManiaDifficultyCalculator::new(ruleset, &beatmap).unwrap();
+
+        assert_eq!(
+            calculator.calculate_without_mods().unwrap(),
+            calculator
+                .calculate_with_collection(&GameMods::default())
+                .unwrap()
+        );
+    }
 }
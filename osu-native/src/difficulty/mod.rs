@@ -1,12 +1,16 @@
 use crate::{
     beatmap::Beatmap,
     error::OsuError,
-    mods::{GameMods, GameModsError, IntoGameMods},
-    ruleset::Ruleset,
+    mods::{GameMods, IntoGameMods},
+    ruleset::{Ruleset, RulesetKind},
     utils::HasNative,
 };
 
+pub mod any;
+#[cfg(feature = "cache")]
+pub mod cache;
 pub mod catch;
+pub mod database;
 pub mod mania;
 pub mod osu;
 pub mod taiko;
@@ -16,8 +20,130 @@ pub trait DifficultyCalculator: Sized {
 
     fn new(ruleset: Ruleset, beatmap: &Beatmap) -> Result<Self, OsuError>;
 
-    fn mods(&self) -> GameMods;
-    fn with_mods(self, mods: impl IntoGameMods) -> Result<Self, GameModsError>;
+    /// The mods used by [`calculate`](Self::calculate)
+    fn mods(&self) -> &GameMods;
+
+    /// Sets the mods used by [`calculate`](Self::calculate)
+    ///
+    /// Shorthand for passing the same mods to
+    /// [`calculate_with_mods`](Self::calculate_with_mods) on every call
+    fn with_mods(self, mods: impl IntoGameMods) -> Result<Self, OsuError>;
 
     fn calculate(&self) -> Result<Self::DifficultyAttributes, OsuError>;
+
+    /// Calculates attributes for `mods`, ignoring the calculator's own mods
+    ///
+    /// Takes `&self` so a single calculator can be shared across mod combinations
+    fn calculate_with_mods(&self, mods: &GameMods) -> Result<Self::DifficultyAttributes, OsuError>;
+
+    /// Calculates attributes for every combination in `mods`, in order
+    ///
+    /// Reuses the calculator and ruleset handles, ignoring the calculator's own mods.
+    /// Combinations that are equivalent for difficulty, e.g. NM and NF or DT and NC,
+    /// are only calculated once
+    fn calculate_batch(
+        &self,
+        mods: &[GameMods],
+    ) -> Result<Vec<Self::DifficultyAttributes>, OsuError>
+    where
+        Self::DifficultyAttributes: Clone,
+    {
+        calculate_batch(None, mods, |mods| self.calculate_with_mods(mods))
+    }
+}
+
+/// Calls `calculate` once per group of difficulty-equivalent combinations in `mods`
+///
+/// Without a `ruleset`, only combinations that are equivalent in every ruleset are grouped
+pub(crate) fn calculate_batch<A: Clone>(
+    ruleset: Option<RulesetKind>,
+    mods: &[GameMods],
+    mut calculate: impl FnMut(&GameMods) -> Result<A, OsuError>,
+) -> Result<Vec<A>, OsuError> {
+    let mut calculated: Vec<(GameMods, A)> = Vec::new();
+
+    mods.iter()
+        .map(|mods| {
+            let key = mods.difficulty_equivalent(ruleset);
+
+            if let Some((_, attributes)) = calculated.iter().find(|(other, _)| *other == key) {
+                return Ok(attributes.clone());
+            }
+
+            let attributes = calculate(mods)?;
+            calculated.push((key, attributes.clone()));
+
+            Ok(attributes)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, str::FromStr};
+
+    use rosu_mods::{Acronym, GameModSimple, simple::SettingSimple};
+
+    use super::calculate_batch;
+    use crate::{mods::GameMods, ruleset::RulesetKind};
+
+    fn mods(acronyms: &[&str]) -> GameMods {
+        GameMods(
+            acronyms
+                .iter()
+                .map(|acronym| GameModSimple {
+                    acronym: Acronym::from_str(acronym).unwrap(),
+                    settings: Default::default(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_batch_deduplicates_equivalent_mods() {
+        let mut custom_rate = mods(&["DT"]);
+        custom_rate.0[0]
+            .settings
+            .insert("speed_change".into(), SettingSimple::Number(1.3));
+
+        let combinations = [
+            mods(&[]),
+            mods(&["NF"]),
+            mods(&["DT"]),
+            mods(&["NC"]),
+            mods(&["HD", "DT"]),
+            mods(&["DT", "HD"]),
+            mods(&["HR"]),
+            custom_rate,
+        ];
+
+        let calls = Cell::new(0);
+        let attributes = calculate_batch(Some(RulesetKind::Osu), &combinations, |mods| {
+            calls.set(calls.get() + 1);
+            Ok(mods.clock_rate())
+        })
+        .unwrap();
+
+        assert_eq!(calls.get(), 5);
+        assert_eq!(attributes, [1.0, 1.0, 1.5, 1.5, 1.5, 1.5, 1.0, 1.3]);
+    }
+
+    #[test]
+    fn test_batch_ruleset_specific_equivalence() {
+        let combinations = [mods(&[]), mods(&["HD"]), mods(&["FL"])];
+        let count_calls = |ruleset| {
+            let calls = Cell::new(0);
+            calculate_batch(ruleset, &combinations, |_| {
+                calls.set(calls.get() + 1);
+                Ok(())
+            })
+            .unwrap();
+
+            calls.get()
+        };
+
+        assert_eq!(count_calls(None), 3);
+        assert_eq!(count_calls(Some(RulesetKind::Osu)), 3);
+        assert_eq!(count_calls(Some(RulesetKind::Mania)), 1);
+    }
 }
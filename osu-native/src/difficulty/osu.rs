@@ -1,15 +1,16 @@
 use std::mem::MaybeUninit;
 
 use libosu_native_sys::{
-    ErrorCode, NativeOsuDifficultyAttributes, OsuDifficultyCalculator_CalculateMods,
-    OsuDifficultyCalculator_Create, OsuDifficultyCalculator_Destroy,
+    ErrorCode, NativeOsuDifficultyAttributes, OsuDifficultyCalculator_Calculate,
+    OsuDifficultyCalculator_CalculateMods, OsuDifficultyCalculator_Create,
+    OsuDifficultyCalculator_Destroy,
 };
 
 use crate::{
     beatmap::Beatmap,
     error::OsuError,
     mods::{
-        GameMods, GameModsError, IntoGameMods,
+        GameMods, IntoGameMods,
         native::{Mod, ModCollection},
     },
     ruleset::Ruleset,
@@ -25,7 +26,64 @@ pub struct OsuDifficultyCalculator {
     mods: GameMods,
 }
 
-impl OsuDifficultyCalculator {}
+impl OsuDifficultyCalculator {
+    /// Calculates through `OsuDifficultyCalculator_Calculate`, skipping the mod collection setup
+    fn calculate_without_mods(&self) -> Result<OsuDifficultyAttributes, OsuError> {
+        let mut attributes = MaybeUninit::uninit();
+
+        let code =
+            unsafe { OsuDifficultyCalculator_Calculate(self.handle, attributes.as_mut_ptr()) };
+
+        if code != ErrorCode::Success {
+            return Err(code.into());
+        }
+
+        let native = unsafe { attributes.assume_init() };
+
+        Ok(native.into())
+    }
+
+    fn calculate_with_collection(
+        &self,
+        game_mods: &GameMods,
+    ) -> Result<OsuDifficultyAttributes, OsuError> {
+        let mod_collection = ModCollection::new()?;
+
+        let mods = game_mods
+            .0
+            .iter()
+            .map(|gamemod| {
+                let m = Mod::new(gamemod.acronym.as_str())?;
+                m.apply_settings(&gamemod.settings)?;
+
+                Ok(m)
+            })
+            .collect::<Result<Vec<_>, OsuError>>()?;
+
+        for gamemod in mods.iter() {
+            mod_collection.add(gamemod)?;
+        }
+
+        let mut attributes = MaybeUninit::uninit();
+
+        let code = unsafe {
+            OsuDifficultyCalculator_CalculateMods(
+                self.handle,
+                self.ruleset.handle(),
+                mod_collection.handle(),
+                attributes.as_mut_ptr(),
+            )
+        };
+
+        if code != ErrorCode::Success {
+            return Err(code.into());
+        }
+
+        let native = unsafe { attributes.assume_init() };
+
+        Ok(native.into())
+    }
+}
 
 impl Drop for OsuDifficultyCalculator {
     fn drop(&mut self) {
@@ -73,11 +131,11 @@ impl DifficultyCalculator for OsuDifficultyCalculator {
         })
     }
 
-    fn mods(&self) -> GameMods {
-        self.mods.clone()
+    fn mods(&self) -> &GameMods {
+        &self.mods
     }
 
-    fn with_mods(mut self, mods: impl IntoGameMods) -> Result<Self, GameModsError> {
+    fn with_mods(mut self, mods: impl IntoGameMods) -> Result<Self, OsuError> {
         self.mods = mods.into_mods()?;
 
         Ok(self)
@@ -103,50 +161,30 @@ impl DifficultyCalculator for OsuDifficultyCalculator {
     ///
     /// Returns [`OsuError::NativeError`] if osu-native returns an error
     fn calculate(&self) -> Result<Self::DifficultyAttributes, OsuError> {
-        let mod_collection = ModCollection::new()?;
-
-        let mods = self
-            .mods
-            .0
-            .iter()
-            .map(|gamemod| {
-                let m = Mod::new(gamemod.acronym.as_str())?;
-                m.apply_settings(&gamemod.settings)?;
-
-                Ok(m)
-            })
-            .collect::<Result<Vec<_>, OsuError>>()?;
-
-        for gamemod in mods.iter() {
-            mod_collection.add(gamemod)?;
-        }
-
-        let mut attributes = MaybeUninit::uninit();
-
-        let code = unsafe {
-            OsuDifficultyCalculator_CalculateMods(
-                self.handle,
-                self.ruleset.handle(),
-                mod_collection.handle(),
-                attributes.as_mut_ptr(),
-            )
-        };
+        self.calculate_with_mods(&self.mods)
+    }
 
-        if code != ErrorCode::Success {
-            return Err(code.into());
+    /// Calculates [`OsuDifficultyAttributes`] for `mods`, ignoring the calculator's own mods
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    fn calculate_with_mods(&self, mods: &GameMods) -> Result<Self::DifficultyAttributes, OsuError> {
+        if mods.is_empty() {
+            self.calculate_without_mods()
+        } else {
+            self.calculate_with_collection(mods)
         }
-
-        let native = unsafe { attributes.assume_init() };
-
-        Ok(native.into())
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuDifficultyAttributes {
     pub star_rating: f64,
     pub max_combo: i32,
     pub aim_difficulty: f64,
+    #[cfg_attr(feature = "serde", serde(rename = "aim_difficult_slider_count"))]
     pub aim_difficulty_slider_count: f64,
     pub speed_difficulty: f64,
     pub speed_note_count: f64,
@@ -230,6 +268,7 @@ mod tests {
     use crate::{
         beatmap::Beatmap,
         difficulty::DifficultyCalculator,
+        mods::{GameMods, IntoGameMods},
         ruleset::{Ruleset, RulesetKind},
         utils::initialize_path,
     };
@@ -282,4 +321,82 @@ mod tests {
         // Panics because of ruleset and calculator don't match
         let _ = OsuDifficultyCalculator::new(ruleset, &beatmap).unwrap();
     }
+
+    #[test]
+    fn test_calculate_without_mods_matches_collection() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let calculator = OsuDifficultyCalculator::new(ruleset, &beatmap).unwrap();
+
+        assert_eq!(
+            calculator.calculate_without_mods().unwrap(),
+            calculator
+                .calculate_with_collection(&GameMods::default())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_calculate_batch_matches_single() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let calculator = OsuDifficultyCalculator::new(ruleset, &beatmap).unwrap();
+
+        let combinations = [0, 8, 16, 64, 72, 512].map(|bits| bits.into_mods().unwrap());
+        let batch = calculator.calculate_batch(&combinations).unwrap();
+
+        for (mods, attributes) in combinations.iter().zip(&batch) {
+            let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+            let single = OsuDifficultyCalculator::new(ruleset, &beatmap)
+                .unwrap()
+                .with_mods(mods)
+                .unwrap()
+                .calculate()
+                .unwrap();
+
+            assert_eq!(*attributes, single);
+        }
+
+        // NC is equivalent to DT
+        assert_eq!(batch[3], batch[5]);
+    }
+
+    #[test]
+    fn test_calculate_with_mods_keeps_calculator_mods() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let calculator = OsuDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .with_mods(16)
+            .unwrap();
+
+        let hard_rock = calculator.calculate().unwrap();
+        let double_time = calculator
+            .calculate_with_mods(&64.into_mods().unwrap())
+            .unwrap();
+
+        assert!(calculator.mods().contains("HR"));
+        assert_ne!(hard_rock, double_time);
+        assert_eq!(calculator.calculate().unwrap(), hard_rock);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let attributes = OsuDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+
+        let json = serde_json::to_value(&attributes).unwrap();
+        assert_eq!(json["max_combo"], 719);
+        assert!(json["aim_difficult_slider_count"].is_f64());
+
+        assert_eq!(
+            serde_json::from_value::<super::OsuDifficultyAttributes>(json).unwrap(),
+            attributes
+        );
+    }
 }
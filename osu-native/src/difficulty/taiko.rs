@@ -1,15 +1,16 @@
 use std::mem::MaybeUninit;
 
 use libosu_native_sys::{
-    ErrorCode, NativeTaikoDifficultyAttributes, TaikoDifficultyCalculator_CalculateMods,
-    TaikoDifficultyCalculator_Create, TaikoDifficultyCalculator_Destroy,
+    ErrorCode, NativeTaikoDifficultyAttributes, TaikoDifficultyCalculator_Calculate,
+    TaikoDifficultyCalculator_CalculateMods, TaikoDifficultyCalculator_Create,
+    TaikoDifficultyCalculator_Destroy,
 };
 
 use crate::{
     beatmap::Beatmap,
     error::OsuError,
     mods::{
-        GameMods, GameModsError, IntoGameMods,
+        GameMods, IntoGameMods,
         native::{Mod, ModCollection},
     },
     ruleset::Ruleset,
@@ -25,6 +26,65 @@ pub struct TaikoDifficultyCalculator {
     mods: GameMods,
 }
 
+impl TaikoDifficultyCalculator {
+    /// Calculates through `TaikoDifficultyCalculator_Calculate`, skipping the mod collection setup
+    fn calculate_without_mods(&self) -> Result<TaikoDifficultyAttributes, OsuError> {
+        let mut attributes = MaybeUninit::uninit();
+
+        let code =
+            unsafe { TaikoDifficultyCalculator_Calculate(self.handle, attributes.as_mut_ptr()) };
+
+        if code != ErrorCode::Success {
+            return Err(code.into());
+        }
+
+        let native = unsafe { attributes.assume_init() };
+
+        Ok(native.into())
+    }
+
+    fn calculate_with_collection(
+        &self,
+        game_mods: &GameMods,
+    ) -> Result<TaikoDifficultyAttributes, OsuError> {
+        let mods = ModCollection::new()?;
+
+        let mods_vec = game_mods
+            .0
+            .iter()
+            .map(|gamemod| {
+                let m = Mod::new(gamemod.acronym.as_str())?;
+                m.apply_settings(&gamemod.settings)?;
+
+                Ok(m)
+            })
+            .collect::<Result<Vec<_>, OsuError>>()?;
+
+        for gamemod in mods_vec.iter() {
+            mods.add(gamemod)?;
+        }
+
+        let mut attributes = MaybeUninit::uninit();
+
+        let code = unsafe {
+            TaikoDifficultyCalculator_CalculateMods(
+                self.handle,
+                self.ruleset.handle(),
+                mods.handle(),
+                attributes.as_mut_ptr(),
+            )
+        };
+
+        if code != ErrorCode::Success {
+            return Err(code.into());
+        }
+
+        let native = unsafe { attributes.assume_init() };
+
+        Ok(native.into())
+    }
+}
+
 impl Drop for TaikoDifficultyCalculator {
     fn drop(&mut self) {
         unsafe { TaikoDifficultyCalculator_Destroy(self.handle) };
@@ -71,11 +131,11 @@ impl DifficultyCalculator for TaikoDifficultyCalculator {
         })
     }
 
-    fn mods(&self) -> GameMods {
-        self.mods.clone()
+    fn mods(&self) -> &GameMods {
+        &self.mods
     }
 
-    fn with_mods(mut self, mods: impl IntoGameMods) -> Result<Self, GameModsError> {
+    fn with_mods(mut self, mods: impl IntoGameMods) -> Result<Self, OsuError> {
         self.mods = mods.into_mods()?;
 
         Ok(self)
@@ -101,46 +161,25 @@ impl DifficultyCalculator for TaikoDifficultyCalculator {
     ///
     /// Returns [`OsuError::NativeError`] if osu-native returns an error
     fn calculate(&self) -> Result<Self::DifficultyAttributes, OsuError> {
-        let mods = ModCollection::new()?;
-
-        let mods_vec = self
-            .mods
-            .0
-            .iter()
-            .map(|gamemod| {
-                let m = Mod::new(gamemod.acronym.as_str())?;
-                m.apply_settings(&gamemod.settings)?;
-
-                Ok(m)
-            })
-            .collect::<Result<Vec<_>, OsuError>>()?;
-
-        for gamemod in mods_vec.iter() {
-            mods.add(gamemod)?;
-        }
-
-        let mut attributes = MaybeUninit::uninit();
-
-        let code = unsafe {
-            TaikoDifficultyCalculator_CalculateMods(
-                self.handle,
-                self.ruleset.handle(),
-                mods.handle(),
-                attributes.as_mut_ptr(),
-            )
-        };
+        self.calculate_with_mods(&self.mods)
+    }
 
-        if code != ErrorCode::Success {
-            return Err(code.into());
+    /// Calculates [`TaikoDifficultyAttributes`] for `mods`, ignoring the calculator's own mods
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OsuError::NativeError`] if osu-native returns an error
+    fn calculate_with_mods(&self, mods: &GameMods) -> Result<Self::DifficultyAttributes, OsuError> {
+        if mods.is_empty() {
+            self.calculate_without_mods()
+        } else {
+            self.calculate_with_collection(mods)
         }
-
-        let native = unsafe { attributes.assume_init() };
-
-        Ok(native.into())
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoDifficultyAttributes {
     pub star_rating: f64,
     pub max_combo: i32,
@@ -202,6 +241,7 @@ mod tests {
         beatmap::Beatmap,
         difficulty::{DifficultyCalculator, taiko::TaikoDifficultyCalculator},
         generate_diffcalc_field_tests,
+        mods::GameMods,
         ruleset::{Ruleset, RulesetKind},
         utils::initialize_path,
     };
@@ -239,4 +279,18 @@ mod tests {
         assert!(attributes_with_mods.star_rating > attributes.star_rating);
         assert!(attributes_with_mods.max_combo == attributes.max_combo);
     }
+
+    #[test]
+    fn test_calculate_without_mods_matches_collection() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Taiko).unwrap();
+        let calculator = TaikoDifficultyCalculator::new(ruleset, &beatmap).unwrap();
+
+        assert_eq!(
+            calculator.calculate_without_mods().unwrap(),
+            calculator
+                .calculate_with_collection(&GameMods::default())
+                .unwrap()
+        );
+    }
 }
@@ -1,7 +1,14 @@
 use libosu_native_sys::ErrorCode;
 use thiserror::Error as ThisError;
 
-use crate::mods::native::{ModCollectionError, ModError};
+use crate::{
+    mods::{
+        GameModsError,
+        native::{ModCollectionError, ModError},
+    },
+    performance::{any::AnyPerformanceError, simulate::UnknownCounts, validation::InvalidScore},
+    ruleset::RulesetError,
+};
 
 #[derive(Debug, ThisError)]
 pub enum NativeError {
@@ -36,14 +43,31 @@ pub enum OsuError {
     LogicError,
     #[error("GameMod error")]
     Mods(#[from] ModError),
+    #[error("Game mods error")]
+    GameMods(#[from] GameModsError),
     #[error("Mod collection error")]
     ModCollection(#[from] ModCollectionError),
     #[error("Native error")]
     NativeError(#[from] NativeError),
+    #[error("Ruleset error")]
+    Ruleset(#[from] RulesetError),
+    #[error("Score validation failed")]
+    InvalidScore(#[from] InvalidScore),
     #[error("Unknown error")]
     UnknownError,
 }
 
+/// Errors of calculations that generate scores and calculate their performance
+#[derive(Debug, ThisError)]
+pub enum CalculationError {
+    #[error("osu-native error")]
+    Osu(#[from] OsuError),
+    #[error("Performance calculation failed")]
+    Performance(#[from] AnyPerformanceError),
+    #[error("Failed to generate a score")]
+    Score(#[from] UnknownCounts),
+}
+
 impl From<ErrorCode> for OsuError {
     fn from(code: ErrorCode) -> Self {
         Self::NativeError(code.into())
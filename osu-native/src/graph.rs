@@ -0,0 +1,383 @@
+//! Difficulty over time, e.g. for plotting a strain graph
+//!
+//! The beatmap is split into sections and each section is calculated as a
+//! beatmap of its own, so a section's star rating reflects only its objects.
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{
+//! #     beatmap::Beatmap,
+//! #     graph::{SectionSplit, StrainGraph},
+//! #     ruleset::RulesetKind,
+//! # };
+//! # let beatmap = Beatmap::from_path("../../standard.osu")?;
+//! let sections = StrainGraph::new(SectionSplit::Window(10_000.0))
+//!     .calculate(&beatmap, RulesetKind::Osu)?;
+//!
+//! for section in sections {
+//!     print!("{:>6}ms {:.2}*", section.start_time, section.star_rating());
+//!
+//!     for (name, value) in section.components() {
+//!         print!(" {name}={value:.2}");
+//!     }
+//!
+//!     println!();
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::cmp::Ordering;
+
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::{Beatmap, BeatmapError, decode::DecodedBeatmap},
+    difficulty::any::{AnyDifficultyAttributes, AnyDifficultyCalculator},
+    error::OsuError,
+    mods::{GameMods, GameModsError, IntoGameMods},
+    partial::PartialSource,
+    ruleset::RulesetKind,
+};
+
+/// Shortest [`SectionSplit::Window`] in milliseconds
+pub const MIN_WINDOW: f64 = 1.0;
+
+/// Most sections a [`SectionSplit::Window`] may split a beatmap into
+pub const MAX_SECTIONS: usize = 10_000;
+
+#[derive(Debug, ThisError)]
+pub enum StrainGraphError {
+    #[error(
+        "Section window must be at least {MIN_WINDOW}ms and make at most {MAX_SECTIONS} sections, got {0}ms"
+    )]
+    InvalidWindow(f64),
+    #[error("Beatmap source has no hit objects")]
+    NoHitObjects,
+    #[error("Failed to load section beatmap")]
+    Beatmap(#[from] BeatmapError),
+    #[error("Difficulty calculation failed")]
+    Calculation(#[from] OsuError),
+}
+
+/// How a beatmap is split into sections
+#[derive(Clone, Debug, PartialEq)]
+pub enum SectionSplit {
+    /// Fixed windows of the given length in milliseconds, starting at the first object
+    Window(f64),
+    /// Sections between the editor bookmarks
+    Bookmarks,
+    /// Sections between kiai toggles
+    Kiai,
+}
+
+/// Difficulty of the objects starting in `start_time..end_time`
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrainSection {
+    pub start_time: f64,
+    pub end_time: f64,
+    pub object_count: usize,
+    /// `None` if the section has no objects
+    pub difficulty: Option<AnyDifficultyAttributes>,
+}
+
+impl StrainSection {
+    /// Star rating of the section, 0 if it's empty
+    pub fn star_rating(&self) -> f64 {
+        self.difficulty
+            .as_ref()
+            .map_or(0.0, AnyDifficultyAttributes::star_rating)
+    }
+
+    /// Named skill difficulties of the ruleset, e.g. aim, speed and flashlight
+    /// for osu!standard
+    ///
+    /// Empty for empty sections and for rulesets without separate skills
+    pub fn components(&self) -> Vec<(&'static str, f64)> {
+        match self.difficulty {
+            Some(AnyDifficultyAttributes::Osu(ref attributes)) => vec![
+                ("aim", attributes.aim_difficulty),
+                ("speed", attributes.speed_difficulty),
+                ("flashlight", attributes.flashlight_difficulty),
+            ],
+            Some(AnyDifficultyAttributes::Taiko(ref attributes)) => vec![
+                ("rhythm", attributes.rhythm_difficulty),
+                ("colour", attributes.colour_difficulty),
+                ("stamina", attributes.stamina_difficulty),
+            ],
+            Some(AnyDifficultyAttributes::Catch(_) | AnyDifficultyAttributes::Mania(_)) | None => {
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Calculates difficulty per section of a beatmap
+#[derive(Clone, Debug)]
+pub struct StrainGraph {
+    split: SectionSplit,
+    mods: GameMods,
+}
+
+impl StrainGraph {
+    pub fn new(split: SectionSplit) -> Self {
+        Self {
+            split,
+            mods: GameMods::default(),
+        }
+    }
+
+    /// Sets the mods applied to every section
+    ///
+    /// # Errors
+    /// Returns a [`GameModsError`] if the mods can't be converted
+    pub fn mods(mut self, mods: impl IntoGameMods) -> Result<Self, GameModsError> {
+        self.mods = mods.into_mods()?;
+
+        Ok(self)
+    }
+
+    /// Calculates the sections of `beatmap` played in `ruleset`, in order
+    ///
+    /// Section times are in beatmap time, i.e. not scaled by rate-changing mods
+    ///
+    /// # Errors
+    /// Returns a [`StrainGraphError::InvalidWindow`] if the window is shorter than
+    /// [`MIN_WINDOW`] or makes more than [`MAX_SECTIONS`] sections
+    /// Returns a [`StrainGraphError`] if the beatmap has no hit objects or a section
+    /// fails to load or calculate
+    pub fn calculate(
+        &self,
+        beatmap: &Beatmap,
+        ruleset: RulesetKind,
+    ) -> Result<Vec<StrainSection>, StrainGraphError> {
+        let source = PartialSource::new(&String::from_utf8_lossy(beatmap.source()));
+
+        let (Some((first, _)), Some((last, _))) = (source.objects.first(), source.objects.last())
+        else {
+            return Err(StrainGraphError::NoHitObjects);
+        };
+
+        if let SectionSplit::Window(window) = self.split {
+            check_window(window, *first, *last)?;
+        }
+
+        let boundaries = boundaries(&self.split, beatmap.decoded(), *first, *last);
+
+        boundaries
+            .windows(2)
+            .map(|bounds| {
+                let (start_time, end_time) = (bounds[0], bounds[1]);
+                let range = source.objects_before(start_time)..source.objects_before(end_time);
+                let object_count = range.len();
+
+                let difficulty = if range.is_empty() {
+                    None
+                } else {
+                    let section = Beatmap::from_text(source.with_objects(range))?;
+                    let attributes = AnyDifficultyCalculator::from_kind(ruleset, &section)?
+                        .calculate_with_mods(&self.mods)?;
+
+                    Some(attributes)
+                };
+
+                Ok(StrainSection {
+                    start_time,
+                    end_time,
+                    object_count,
+                    difficulty,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Checks that a window makes a sensible number of sections for objects from
+/// `first` to `last`
+fn check_window(window: f64, first: f64, last: f64) -> Result<(), StrainGraphError> {
+    if window.partial_cmp(&MIN_WINDOW).is_none_or(Ordering::is_lt) {
+        return Err(StrainGraphError::InvalidWindow(window));
+    }
+
+    if (last - first) / window >= MAX_SECTIONS as f64 {
+        return Err(StrainGraphError::InvalidWindow(window));
+    }
+
+    Ok(())
+}
+
+/// Ascending section boundaries from the first object's start time `first` to past
+/// the last object's start time `last`
+fn boundaries(split: &SectionSplit, decoded: &DecodedBeatmap, first: f64, last: f64) -> Vec<f64> {
+    let mut inner: Vec<f64> = match *split {
+        SectionSplit::Window(window) => {
+            let mut bounds = vec![first];
+            let mut time = first + window;
+
+            while time <= last {
+                bounds.push(time);
+                time += window;
+            }
+
+            bounds.push(time);
+
+            return bounds;
+        }
+        SectionSplit::Bookmarks => decoded.bookmarks.clone(),
+        SectionSplit::Kiai => {
+            let mut kiai = false;
+
+            decoded
+                .timing_points
+                .iter()
+                .filter(|point| {
+                    let toggled = point.kiai != kiai;
+                    kiai = point.kiai;
+
+                    toggled
+                })
+                .map(|point| point.time)
+                .collect()
+        }
+    };
+
+    inner.sort_by(f64::total_cmp);
+
+    let mut bounds = vec![first];
+    bounds.extend(
+        inner
+            .into_iter()
+            .filter(|time| first < *time && *time <= last),
+    );
+    bounds.dedup();
+    // Past the last object, so it's part of the final section
+    bounds.push(last + 1.0);
+
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        MAX_SECTIONS, SectionSplit, StrainGraph, StrainGraphError, StrainSection, boundaries,
+        check_window,
+    };
+    use crate::{
+        beatmap::{Beatmap, decode::DecodedBeatmap},
+        difficulty::mania::ManiaDifficultyAttributes,
+        partial::PartialSource,
+        ruleset::RulesetKind,
+        utils::initialize_path,
+    };
+
+    const TEXT: &str = "osu file format v14
+
+[Editor]
+Bookmarks: 500,2000,9000
+
+[TimingPoints]
+0,500,4,2,0,100,1,0
+1000,-100,4,2,0,100,0,1
+2500,-100,4,2,0,100,0,0
+3000,-100,4,2,0,100,0,0
+
+[HitObjects]
+256,192,1000,1,0,0:0:0:0:
+256,192,2000,1,0,0:0:0:0:
+256,192,3000,1,0,0:0:0:0:
+";
+
+    fn bounds(split: SectionSplit) -> Vec<f64> {
+        boundaries(&split, &DecodedBeatmap::from_text(TEXT), 1000.0, 3000.0)
+    }
+
+    #[test]
+    fn test_window_boundaries() {
+        assert_eq!(
+            bounds(SectionSplit::Window(1000.0)),
+            [1000.0, 2000.0, 3000.0, 4000.0]
+        );
+        assert_eq!(bounds(SectionSplit::Window(5000.0)), [1000.0, 6000.0]);
+    }
+
+    #[test]
+    fn test_bookmark_and_kiai_boundaries() {
+        // Bookmarks outside of the objects are ignored
+        assert_eq!(bounds(SectionSplit::Bookmarks), [1000.0, 2000.0, 3001.0]);
+        // Kiai starts with the first object and ends at 2500
+        assert_eq!(bounds(SectionSplit::Kiai), [1000.0, 2500.0, 3001.0]);
+    }
+
+    #[test]
+    fn test_section_components() {
+        let empty = StrainSection {
+            start_time: 0.0,
+            end_time: 1000.0,
+            object_count: 0,
+            difficulty: None,
+        };
+        assert_eq!(empty.star_rating(), 0.0);
+        assert!(empty.components().is_empty());
+
+        let mania = StrainSection {
+            difficulty: Some(
+                ManiaDifficultyAttributes {
+                    star_rating: 3.0,
+                    max_combo: 100,
+                }
+                .into(),
+            ),
+            ..empty
+        };
+        assert_eq!(mania.star_rating(), 3.0);
+        assert!(mania.components().is_empty());
+    }
+
+    #[test]
+    fn test_window_limits() {
+        assert!(check_window(1.0, 0.0, 1000.0).is_ok());
+        assert!(check_window(10.0, 0.0, 10.0 * (MAX_SECTIONS - 1) as f64).is_ok());
+
+        for window in [0.0, -1.0, 0.5, 1e-12, f64::NAN] {
+            assert!(matches!(
+                check_window(window, 0.0, 1000.0),
+                Err(StrainGraphError::InvalidWindow(_))
+            ));
+        }
+
+        assert!(matches!(
+            check_window(1.0, 0.0, 100_000.0),
+            Err(StrainGraphError::InvalidWindow(1.0))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_window() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+
+        assert!(matches!(
+            StrainGraph::new(SectionSplit::Window(0.0)).calculate(&beatmap, RulesetKind::Osu),
+            Err(StrainGraphError::InvalidWindow(_))
+        ));
+    }
+
+    #[test]
+    fn test_strain_graph() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let sections = StrainGraph::new(SectionSplit::Window(10_000.0))
+            .calculate(&beatmap, RulesetKind::Osu)
+            .unwrap();
+
+        let source = PartialSource::new(&String::from_utf8_lossy(beatmap.source()));
+
+        assert!(sections.len() > 1);
+        assert_eq!(
+            sections.iter().map(|s| s.object_count).sum::<usize>(),
+            source.objects.len()
+        );
+
+        for section in sections.iter().filter(|s| s.object_count > 0) {
+            assert!(section.star_rating() > 0.0);
+            assert_eq!(section.components().len(), 3);
+        }
+    }
+}
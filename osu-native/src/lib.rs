@@ -1,7 +1,13 @@
 pub mod beatmap;
 pub mod difficulty;
 pub mod error;
+pub mod graph;
 pub mod mods;
+pub mod partial;
 pub mod performance;
+pub mod pp_solver;
+pub mod pp_table;
+pub mod rate;
 pub mod ruleset;
+pub mod scanner;
 pub mod utils;
@@ -3,13 +3,25 @@ use std::{
     fmt::{Debug, Formatter, Result as FmtResult},
 };
 
-use rosu_mods::{GameModSimple, GameMods as GameModsLazer, GameModsIntermode, GameModsLegacy};
+use rosu_mods::{
+    GameModSimple, GameMods as GameModsLazer, GameModsIntermode, GameModsLegacy,
+    simple::SettingSimple,
+};
 use serde_json::Error as JsonError;
 use thiserror::Error as ThisError;
 
+use crate::ruleset::RulesetKind;
+
 pub mod native;
 
+/// With the `serde` feature, serialized as a list of lazer's API mods,
+/// e.g. `[{"acronym":"DT","settings":{"speed_change":1.3}}]`
 #[derive(Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct GameMods(pub(crate) Vec<GameModSimple>);
 
 /// Convenience trait to turn a type into [`GameMods`]
@@ -24,6 +36,98 @@ pub trait IntoGameMods {
 
 impl GameMods {
     pub(crate) const DEFAULT: Self = Self(Vec::new());
+
+    /// Clock rate applied by the rate-changing mods in the collection
+    ///
+    /// Respects custom `speed_change` settings on DT/NC/HT/DC. Mods that change
+    /// the rate over time (WU/WD/AS) contribute their initial rate, same as song select
+    pub fn clock_rate(&self) -> f64 {
+        self.0
+            .iter()
+            .map(|gamemod| match gamemod.acronym.as_str() {
+                "DT" | "NC" => number_setting(gamemod, "speed_change").unwrap_or(1.5),
+                "HT" | "DC" => number_setting(gamemod, "speed_change").unwrap_or(0.75),
+                "WU" | "WD" | "AS" => number_setting(gamemod, "initial_rate").unwrap_or(1.0),
+                _ => 1.0,
+            })
+            .product()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether a mod with the given acronym is in the collection
+    pub fn contains(&self, acronym: &str) -> bool {
+        self.get(acronym).is_some()
+    }
+
+    /// Normalized copy of the collection for comparing combinations by their effect
+    /// on difficulty in `ruleset`, or in every ruleset if it's `None`
+    ///
+    /// Drops mods that never change difficulty, treats NC as DT and DC as HT,
+    /// ignores pitch settings and sorts the remaining mods by acronym
+    pub(crate) fn difficulty_equivalent(&self, ruleset: Option<RulesetKind>) -> Self {
+        let mut mods: Vec<_> = self
+            .0
+            .iter()
+            .filter(|gamemod| affects_difficulty(ruleset, gamemod.acronym.as_str()))
+            .map(|gamemod| {
+                let mut gamemod = gamemod.clone();
+
+                let acronym = match gamemod.acronym.as_str() {
+                    "NC" => Some("DT"),
+                    "DC" => Some("HT"),
+                    _ => None,
+                };
+
+                if let Some(acronym) = acronym.and_then(|acronym| acronym.parse().ok()) {
+                    gamemod.acronym = acronym;
+                }
+
+                gamemod.settings.remove("adjust_pitch");
+
+                gamemod
+            })
+            .collect();
+
+        mods.sort_by(|a, b| a.acronym.as_str().cmp(b.acronym.as_str()));
+
+        Self(mods)
+    }
+
+    pub(crate) fn get(&self, acronym: &str) -> Option<&GameModSimple> {
+        self.0
+            .iter()
+            .find(|gamemod| gamemod.acronym.as_str() == acronym)
+    }
+}
+
+/// Whether a mod can change difficulty attributes in `ruleset`, or in any ruleset
+/// if it's `None`
+///
+/// Errs on the side of `true` for mods that aren't known to be irrelevant
+fn affects_difficulty(ruleset: Option<RulesetKind>, acronym: &str) -> bool {
+    let ignored = match ruleset {
+        Some(RulesetKind::Mania) => matches!(acronym, "NF" | "SD" | "PF" | "HD" | "FI" | "FL"),
+        Some(RulesetKind::Catch) => matches!(acronym, "NF" | "SD" | "PF" | "HD" | "FL"),
+        Some(RulesetKind::Osu | RulesetKind::Taiko) | None => {
+            matches!(acronym, "NF" | "SD" | "PF")
+        }
+    };
+
+    !ignored
+}
+
+pub(crate) fn number_setting(gamemod: &GameModSimple, key: &str) -> Option<f64> {
+    match gamemod.settings.get(key)? {
+        SettingSimple::Number(value) => Some(*value),
+        _ => None,
+    }
 }
 
 impl Debug for GameMods {
@@ -46,6 +150,18 @@ pub enum GameModsError {
     Deserialization(#[source] JsonError),
 }
 
+impl IntoGameMods for GameMods {
+    fn into_mods(self) -> Result<GameMods, GameModsError> {
+        Ok(self)
+    }
+}
+
+impl IntoGameMods for &GameMods {
+    fn into_mods(self) -> Result<GameMods, GameModsError> {
+        Ok(self.clone())
+    }
+}
+
 impl IntoGameMods for &GameModsLazer {
     fn into_mods(self) -> Result<GameMods, GameModsError> {
         let serialized = serde_json::to_vec(self).map_err(GameModsError::Serialization)?;
@@ -86,3 +202,20 @@ impl IntoGameMods for Vec<GameModSimple> {
         Ok(GameMods(self))
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::GameMods;
+
+    #[test]
+    fn test_game_mods_serde() {
+        let json = r#"[{"acronym":"HD"},{"acronym":"DT","settings":{"speed_change":1.3}}]"#;
+        let mods: GameMods = serde_json::from_str(json).unwrap();
+
+        assert!(mods.contains("HD"));
+        assert_eq!(mods.clock_rate(), 1.3);
+
+        let serialized = serde_json::to_string(&mods).unwrap();
+        assert_eq!(serde_json::from_str::<GameMods>(&serialized).unwrap(), mods);
+    }
+}
@@ -0,0 +1,357 @@
+//! Difficulty and performance of the first objects of a beatmap, e.g. for
+//! showing the current pp while a map is being played
+//!
+//! osu-native can only calculate whole beatmaps, so the .osu source is split
+//! once into its hit object lines and every object count is calculated on its
+//! own truncated copy of the beatmap.
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{
+//! #     beatmap::Beatmap,
+//! #     partial::PartialCalculator,
+//! #     performance::ScoreStatistics,
+//! #     ruleset::RulesetKind,
+//! # };
+//! # let beatmap = Beatmap::from_path("../../standard.osu")?;
+//! let mut calculator = PartialCalculator::new(&beatmap, RulesetKind::Osu)?.with_mods(8)?;
+//!
+//! // 30 seconds into the map
+//! let count = calculator.objects_until(30_000.0);
+//! let score = ScoreStatistics {
+//!     max_combo: 120,
+//!     count_great: 95,
+//!     count_ok: 5,
+//!     ..Default::default()
+//! };
+//!
+//! let performance = calculator.performance(count, &score)?;
+//! println!("{:.2}pp after {count} objects", performance.pp());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{collections::HashMap, ops::Range};
+
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::{Beatmap, BeatmapError, decode::Section},
+    difficulty::any::{AnyDifficultyAttributes, AnyDifficultyCalculator},
+    error::OsuError,
+    mods::{GameMods, GameModsError, IntoGameMods},
+    performance::{
+        ScoreStatistics,
+        any::{AnyPerformanceAttributes, AnyPerformanceCalculator, AnyPerformanceError},
+    },
+    ruleset::{Ruleset, RulesetKind},
+};
+
+#[derive(Debug, ThisError)]
+pub enum PartialError {
+    #[error("Beatmap source has no hit objects")]
+    NoHitObjects,
+    #[error("Failed to load truncated beatmap")]
+    Beatmap(#[from] BeatmapError),
+    #[error("Difficulty calculation failed")]
+    Calculation(#[from] OsuError),
+    #[error("Performance calculation failed")]
+    Performance(#[from] AnyPerformanceError),
+}
+
+/// A .osu file split into everything but the hit objects and the hit object lines
+///
+/// Hit object lines are sorted by start time, keeping the file order of objects
+/// sharing a time
+#[derive(Debug, PartialEq)]
+pub(crate) struct PartialSource {
+    head: String,
+    pub(crate) objects: Vec<(f64, String)>,
+}
+
+impl PartialSource {
+    pub(crate) fn new(text: &str) -> Self {
+        let mut head = String::new();
+        let mut objects = Vec::new();
+        let mut section = None;
+
+        for line in text.lines() {
+            let trimmed = line.trim();
+
+            if let Some(next) = Section::from_header(trimmed) {
+                section = Some(next);
+
+                if next == Section::HitObjects {
+                    continue;
+                }
+            }
+
+            if section == Some(Section::HitObjects) {
+                if let Some(time) = start_time(trimmed) {
+                    objects.push((time, trimmed.to_owned()));
+                }
+            } else {
+                head.push_str(line);
+                head.push('\n');
+            }
+        }
+
+        // .osu files aren't required to list objects in order
+        objects.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        Self { head, objects }
+    }
+
+    /// Number of objects starting at or before `time`
+    pub(crate) fn objects_until(&self, time: f64) -> usize {
+        self.objects
+            .partition_point(|(start_time, _)| *start_time <= time)
+    }
+
+    /// Number of objects starting before `time`
+    pub(crate) fn objects_before(&self, time: f64) -> usize {
+        self.objects
+            .partition_point(|(start_time, _)| *start_time < time)
+    }
+
+    fn truncated(&self, count: usize) -> String {
+        self.with_objects(0..count.min(self.objects.len()))
+    }
+
+    /// .osu contents with only the hit objects in `range`
+    pub(crate) fn with_objects(&self, range: Range<usize>) -> String {
+        let mut text = self.head.clone();
+        text.push_str("[HitObjects]\n");
+
+        for (_, line) in &self.objects[range] {
+            text.push_str(line);
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+/// Start time of a hit object line, `None` for anything else
+fn start_time(line: &str) -> Option<f64> {
+    if line.starts_with("//") {
+        return None;
+    }
+
+    line.split(',').nth(2)?.trim().parse().ok()
+}
+
+/// Calculates difficulty and performance for the first objects of a beatmap
+///
+/// Nothing carries over between object counts: each new count loads its
+/// truncated beatmap and calculates its difficulty from scratch natively.
+/// Difficulty attributes are cached per count and the last truncated beatmap
+/// is kept, so asking for the same count again doesn't reload or recalculate it
+pub struct PartialCalculator {
+    source: PartialSource,
+    ruleset: Ruleset,
+    mods: GameMods,
+    performance: AnyPerformanceCalculator,
+    attributes: HashMap<usize, AnyDifficultyAttributes>,
+    current: Option<(usize, Beatmap)>,
+}
+
+impl PartialCalculator {
+    /// Prepares partial calculations of `beatmap` played in `ruleset`
+    ///
+    /// # Errors
+    /// Returns a [`PartialError::NoHitObjects`] if the beatmap's source has no hit objects
+    /// Returns a [`PartialError::Calculation`] if the native ruleset or performance
+    /// calculator can't be created
+    pub fn new(beatmap: &Beatmap, ruleset: RulesetKind) -> Result<Self, PartialError> {
+        let source = PartialSource::new(&String::from_utf8_lossy(beatmap.source()));
+
+        if source.objects.is_empty() {
+            return Err(PartialError::NoHitObjects);
+        }
+
+        Ok(Self {
+            source,
+            ruleset: Ruleset::new(ruleset).map_err(OsuError::from)?,
+            mods: GameMods::default(),
+            performance: AnyPerformanceCalculator::new(ruleset).map_err(OsuError::from)?,
+            attributes: HashMap::new(),
+            current: None,
+        })
+    }
+
+    /// Sets the mods for all calculations, dropping cached attributes
+    ///
+    /// # Errors
+    /// Returns a [`GameModsError`] if the mods can't be converted
+    pub fn with_mods(mut self, mods: impl IntoGameMods) -> Result<Self, GameModsError> {
+        self.mods = mods.into_mods()?;
+        self.attributes.clear();
+
+        Ok(self)
+    }
+
+    pub fn mods(&self) -> &GameMods {
+        &self.mods
+    }
+
+    /// Number of hit objects in the full beatmap
+    pub fn object_count(&self) -> usize {
+        self.source.objects.len()
+    }
+
+    /// Number of hit objects starting at or before `time` in milliseconds
+    pub fn objects_until(&self, time: f64) -> usize {
+        self.source.objects_until(time)
+    }
+
+    /// .osu contents of the beatmap cut off after `count` hit objects
+    pub fn to_osu_string(&self, count: usize) -> String {
+        self.source.truncated(count)
+    }
+
+    /// Difficulty attributes of the first `count` hit objects
+    ///
+    /// `count` is clamped to [`object_count`](Self::object_count)
+    ///
+    /// # Errors
+    /// Returns a [`PartialError`] if the truncated beatmap can't be loaded or the
+    /// calculation fails
+    pub fn difficulty(&mut self, count: usize) -> Result<AnyDifficultyAttributes, PartialError> {
+        let count = count.min(self.object_count());
+
+        if let Some(attributes) = self.attributes.get(&count) {
+            return Ok(attributes.clone());
+        }
+
+        let beatmap = load(&mut self.current, &self.source, count)?;
+        let attributes = AnyDifficultyCalculator::from_kind(self.ruleset.kind, beatmap)?
+            .calculate_with_mods(&self.mods)?;
+
+        self.attributes.insert(count, attributes.clone());
+
+        Ok(attributes)
+    }
+
+    /// Performance of `score` on the first `count` hit objects
+    ///
+    /// `count` is clamped to [`object_count`](Self::object_count)
+    ///
+    /// # Errors
+    /// Returns a [`PartialError`] if the truncated beatmap can't be loaded or a
+    /// calculation fails
+    pub fn performance(
+        &mut self,
+        count: usize,
+        score: &ScoreStatistics,
+    ) -> Result<AnyPerformanceAttributes, PartialError> {
+        let count = count.min(self.object_count());
+        let difficulty = self.difficulty(count)?;
+        let beatmap = load(&mut self.current, &self.source, count)?;
+
+        let attributes =
+            self.performance
+                .calculate(&self.ruleset, score, beatmap, &self.mods, &difficulty)?;
+
+        Ok(attributes)
+    }
+}
+
+/// The truncated beatmap for `count`, only loading it if the count changed
+fn load<'a>(
+    current: &'a mut Option<(usize, Beatmap)>,
+    source: &PartialSource,
+    count: usize,
+) -> Result<&'a Beatmap, PartialError> {
+    let beatmap = match current.take() {
+        Some((loaded, beatmap)) if loaded == count => beatmap,
+        _ => Beatmap::from_text(source.truncated(count))?,
+    };
+
+    Ok(&current.insert((count, beatmap)).1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PartialCalculator, PartialSource};
+    use crate::{
+        beatmap::{Beatmap, builder::BeatmapBuilder},
+        performance::ScoreStatistics,
+        ruleset::RulesetKind,
+        utils::initialize_path,
+    };
+
+    fn source() -> PartialSource {
+        let text = BeatmapBuilder::new(RulesetKind::Osu)
+            .timing_point(0.0, 120.0)
+            .circle(500.0, (100.0, 100.0))
+            .circle(1000.0, (200.0, 100.0))
+            .slider(1500.0, (100.0, 200.0), &[(300.0, 200.0)], 1, 200.0)
+            .spinner(3000.0, 4000.0)
+            .to_osu_string();
+
+        PartialSource::new(&text)
+    }
+
+    #[test]
+    fn test_source_split() {
+        let source = source();
+
+        assert_eq!(source.objects.len(), 4);
+        assert!(source.head.contains("[TimingPoints]"));
+        assert!(!source.head.contains("[HitObjects]"));
+        assert_eq!(source.objects[2].0, 1500.0);
+    }
+
+    #[test]
+    fn test_source_sorts_objects() {
+        let source = PartialSource::new(
+            "[General]\nMode: 0\n\n[HitObjects]\n\
+             256,192,2000,1,0\n256,192,500,1,0\n100,100,1000,1,0\n200,200,1000,1,0\n",
+        );
+        let times: Vec<_> = source.objects.iter().map(|(time, _)| *time).collect();
+
+        assert_eq!(times, [500.0, 1000.0, 1000.0, 2000.0]);
+        assert_eq!(source.objects[1].1, "100,100,1000,1,0");
+        assert_eq!(source.objects_until(1000.0), 3);
+    }
+
+    #[test]
+    fn test_objects_until() {
+        let source = source();
+
+        assert_eq!(source.objects_until(0.0), 0);
+        assert_eq!(source.objects_until(1000.0), 2);
+        assert_eq!(source.objects_until(2000.0), 3);
+        assert_eq!(source.objects_until(10_000.0), 4);
+    }
+
+    #[test]
+    fn test_truncated_source() {
+        let source = source();
+        let truncated = PartialSource::new(&source.truncated(2));
+
+        assert_eq!(truncated.head, source.head);
+        assert_eq!(truncated.objects, source.objects[..2]);
+    }
+
+    #[test]
+    fn test_partial_difficulty() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let mut calculator = PartialCalculator::new(&beatmap, RulesetKind::Osu).unwrap();
+        let total = calculator.object_count();
+
+        let half = calculator.difficulty(total / 2).unwrap();
+        let full = calculator.difficulty(total).unwrap();
+
+        assert!(half.max_combo() < full.max_combo());
+        assert_eq!(full.max_combo(), 719);
+        assert_eq!(calculator.difficulty(total / 2).unwrap(), half);
+
+        let score = ScoreStatistics {
+            max_combo: half.max_combo(),
+            count_great: i32::try_from(total / 2).unwrap(),
+            ..Default::default()
+        };
+        assert!(calculator.performance(total / 2, &score).unwrap().pp() > 0.0);
+    }
+}
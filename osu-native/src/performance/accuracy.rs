@@ -0,0 +1,312 @@
+//! Accuracy of a score from its hit counts
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{
+//! #     performance::{ScoreStatistics, accuracy::{Accuracy, AccuracyMode}},
+//! #     ruleset::RulesetKind,
+//! # };
+//! let mut score = ScoreStatistics {
+//!     max_combo: 700,
+//!     count_great: 470,
+//!     count_ok: 10,
+//!     count_miss: 2,
+//!     count_slider_tail_hit: 178,
+//!     ..Default::default()
+//! };
+//!
+//! let accuracy = Accuracy::new(RulesetKind::Osu).sliders(180).large_ticks(90);
+//! accuracy.apply(&mut score);
+//! assert!(accuracy.validate(&score).is_ok());
+//! ```
+
+use thiserror::Error as ThisError;
+
+use crate::{mods::GameMods, performance::ScoreStatistics, ruleset::RulesetKind};
+
+/// Largest difference between a supplied accuracy and the counts' accuracy
+/// that is still considered the same, i.e. 0.01%
+pub const ACCURACY_TOLERANCE: f64 = 1e-4;
+
+#[derive(Debug, ThisError, PartialEq)]
+pub enum AccuracyError {
+    #[error("Supplied accuracy {supplied} doesn't match the counts' accuracy {computed}")]
+    Mismatch { supplied: f64, computed: f64 },
+}
+
+/// Which hit results count towards accuracy and how much they're worth
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AccuracyMode {
+    /// Stable scoring, also used by lazer with the Classic mod in osu!: sliders
+    /// only count as a single circle and mania 320s are worth as much as 300s
+    Classic,
+    /// Lazer scoring: osu! slider tails and large ticks count too and mania
+    /// 320s are worth 305
+    #[default]
+    Lazer,
+}
+
+impl AccuracyMode {
+    /// [`Classic`](Self::Classic) with CL, [`Lazer`](Self::Lazer) otherwise
+    pub fn from_mods(mods: &GameMods) -> Self {
+        if mods.contains("CL") {
+            Self::Classic
+        } else {
+            Self::Lazer
+        }
+    }
+}
+
+/// Computes and checks the accuracy of scores in a ruleset
+///
+/// Lazer osu! accuracy also needs the beatmap's slider count and its number of
+/// large ticks, i.e. slider ticks and repeats. Without the latter, ticks are
+/// left out of the accuracy entirely
+#[derive(Clone, Debug, PartialEq)]
+pub struct Accuracy {
+    ruleset: RulesetKind,
+    mode: AccuracyMode,
+    sliders: i32,
+    large_ticks: Option<i32>,
+}
+
+impl Accuracy {
+    pub fn new(ruleset: RulesetKind) -> Self {
+        Self {
+            ruleset,
+            mode: AccuracyMode::default(),
+            sliders: 0,
+            large_ticks: None,
+        }
+    }
+
+    pub fn mode(mut self, mode: AccuracyMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Number of osu! sliders in the beatmap
+    pub fn sliders(mut self, sliders: i32) -> Self {
+        self.sliders = sliders;
+        self
+    }
+
+    /// Number of osu! slider ticks and repeats in the beatmap
+    pub fn large_ticks(mut self, large_ticks: i32) -> Self {
+        self.large_ticks = Some(large_ticks);
+        self
+    }
+
+    /// Accuracy between 0 and 1 of the score's counts, ignoring its
+    /// `accuracy` field
+    ///
+    /// 1 if the score has no hit results
+    pub fn of(&self, score: &ScoreStatistics) -> f64 {
+        let (points, max) = match self.ruleset {
+            RulesetKind::Osu => self.osu(score),
+            RulesetKind::Taiko => {
+                let total = score.count_great + score.count_ok + score.count_miss;
+
+                (2 * score.count_great + score.count_ok, 2 * total)
+            }
+            RulesetKind::Catch => {
+                let hits = score.count_great + score.count_ok + score.count_meh;
+
+                (hits, hits + score.count_good + score.count_miss)
+            }
+            RulesetKind::Mania => self.mania(score),
+        };
+
+        if max <= 0 {
+            1.0
+        } else {
+            (f64::from(points) / f64::from(max)).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Sets the score's `accuracy` field from its counts
+    pub fn apply(&self, score: &mut ScoreStatistics) {
+        score.accuracy = self.of(score);
+    }
+
+    /// Checks that the score's `accuracy` field matches its counts within
+    /// [`ACCURACY_TOLERANCE`]
+    ///
+    /// # Errors
+    /// Returns an [`AccuracyError::Mismatch`] if it doesn't
+    pub fn validate(&self, score: &ScoreStatistics) -> Result<(), AccuracyError> {
+        let computed = self.of(score);
+
+        if (score.accuracy - computed).abs() <= ACCURACY_TOLERANCE {
+            Ok(())
+        } else {
+            Err(AccuracyError::Mismatch {
+                supplied: score.accuracy,
+                computed,
+            })
+        }
+    }
+
+    fn osu(&self, score: &ScoreStatistics) -> (i32, i32) {
+        let objects = score.count_great + score.count_ok + score.count_meh + score.count_miss;
+        let mut points = 300 * score.count_great + 100 * score.count_ok + 50 * score.count_meh;
+        let mut max = 300 * objects;
+
+        if self.mode == AccuracyMode::Lazer {
+            points += 150 * score.count_slider_tail_hit;
+            max += 150 * self.sliders;
+
+            if let Some(large_ticks) = self.large_ticks {
+                points += 30 * (large_ticks - score.count_large_tick_miss).max(0);
+                max += 30 * large_ticks;
+            }
+        }
+
+        (points, max)
+    }
+
+    fn mania(&self, score: &ScoreStatistics) -> (i32, i32) {
+        let perfect = match self.mode {
+            AccuracyMode::Classic => 300,
+            AccuracyMode::Lazer => 305,
+        };
+        let total = score.count_perfect
+            + score.count_great
+            + score.count_good
+            + score.count_ok
+            + score.count_meh
+            + score.count_miss;
+
+        let points = perfect * score.count_perfect
+            + 300 * score.count_great
+            + 200 * score.count_good
+            + 100 * score.count_ok
+            + 50 * score.count_meh;
+
+        (points, perfect * total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rosu_mods::{Acronym, GameModSimple};
+
+    use super::{Accuracy, AccuracyError, AccuracyMode};
+    use crate::{mods::GameMods, performance::ScoreStatistics, ruleset::RulesetKind};
+
+    fn osu_score() -> ScoreStatistics {
+        ScoreStatistics {
+            count_great: 90,
+            count_ok: 6,
+            count_meh: 2,
+            count_miss: 2,
+            count_slider_tail_hit: 38,
+            count_large_tick_miss: 5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_osu_classic_accuracy() {
+        let accuracy = Accuracy::new(RulesetKind::Osu).mode(AccuracyMode::Classic);
+
+        assert_eq!(accuracy.of(&osu_score()), 27_700.0 / 30_000.0);
+    }
+
+    #[test]
+    fn test_osu_lazer_accuracy() {
+        let accuracy = Accuracy::new(RulesetKind::Osu).sliders(40);
+        assert_eq!(
+            accuracy.of(&osu_score()),
+            (27_700.0 + 38.0 * 150.0) / (30_000.0 + 40.0 * 150.0)
+        );
+
+        let accuracy = accuracy.large_ticks(50);
+        assert_eq!(
+            accuracy.of(&osu_score()),
+            (27_700.0 + 38.0 * 150.0 + 45.0 * 30.0) / (30_000.0 + 40.0 * 150.0 + 50.0 * 30.0)
+        );
+    }
+
+    #[test]
+    fn test_mania_accuracy_modes() {
+        let score = ScoreStatistics {
+            count_perfect: 10,
+            count_great: 10,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Accuracy::new(RulesetKind::Mania)
+                .mode(AccuracyMode::Classic)
+                .of(&score),
+            1.0
+        );
+        assert_eq!(
+            Accuracy::new(RulesetKind::Mania).of(&score),
+            6050.0 / 6100.0
+        );
+    }
+
+    #[test]
+    fn test_taiko_and_catch_accuracy() {
+        let taiko = ScoreStatistics {
+            count_great: 8,
+            count_ok: 2,
+            ..Default::default()
+        };
+        assert_eq!(Accuracy::new(RulesetKind::Taiko).of(&taiko), 0.9);
+
+        let catch = ScoreStatistics {
+            count_great: 50,
+            count_ok: 20,
+            count_meh: 25,
+            count_good: 4,
+            count_miss: 1,
+            ..Default::default()
+        };
+        assert_eq!(Accuracy::new(RulesetKind::Catch).of(&catch), 0.95);
+        assert_eq!(
+            Accuracy::new(RulesetKind::Catch).of(&ScoreStatistics::default()),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_apply_and_validate() {
+        let accuracy = Accuracy::new(RulesetKind::Taiko);
+        let mut score = ScoreStatistics {
+            count_great: 8,
+            count_miss: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            accuracy.validate(&score),
+            Err(AccuracyError::Mismatch {
+                supplied: 1.0,
+                computed: 0.8
+            })
+        );
+
+        accuracy.apply(&mut score);
+        assert_eq!(score.accuracy, 0.8);
+        assert!(accuracy.validate(&score).is_ok());
+    }
+
+    #[test]
+    fn test_mode_from_mods() {
+        let classic = GameMods(vec![GameModSimple {
+            acronym: Acronym::from_str("CL").unwrap(),
+            settings: Default::default(),
+        }]);
+
+        assert_eq!(AccuracyMode::from_mods(&classic), AccuracyMode::Classic);
+        assert_eq!(
+            AccuracyMode::from_mods(&GameMods::default()),
+            AccuracyMode::Lazer
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::Beatmap,
+    difficulty::any::AnyDifficultyAttributes,
+    error::{NativeError, OsuError},
+    mods::{IntoGameMods, native::ModCollection},
+    performance::{
+        PerformanceCalculator, ScoreStatistics,
+        catch::{CatchPerformanceAttributes, CatchPerformanceCalculator},
+        mania::{ManiaPerformanceAttributes, ManiaPerformanceCalculator},
+        osu::{OsuPerformanceAttributes, OsuPerformanceCalculator},
+        taiko::{TaikoPerformanceAttributes, TaikoPerformanceCalculator},
+    },
+    ruleset::{Ruleset, RulesetKind, RulesetMismatch},
+};
+
+#[derive(Debug, ThisError)]
+pub enum AnyPerformanceError {
+    #[error("Ruleset or difficulty attributes don't match the calculator")]
+    Mismatch(#[from] RulesetMismatch),
+    #[error("Performance calculation failed")]
+    Calculation(#[from] OsuError),
+}
+
+/// Performance calculator for a ruleset only known at runtime
+///
+/// # Examples
+/// ```no_run
+/// # use osu_native::{
+/// #     beatmap::Beatmap,
+/// #     difficulty::any::AnyDifficultyCalculator,
+/// #     performance::{ScoreStatistics, any::AnyPerformanceCalculator},
+/// #     ruleset::Ruleset,
+/// # };
+/// # let beatmap = Beatmap::from_path("../../standard.osu")?;
+/// let kind = beatmap.ruleset()?;
+/// let difficulty = AnyDifficultyCalculator::from_kind(kind, &beatmap)?.calculate()?;
+///
+/// let score = ScoreStatistics {
+///     max_combo: difficulty.max_combo(),
+///     ..Default::default()
+/// };
+/// let ruleset = Ruleset::new(kind)?;
+/// let performance = AnyPerformanceCalculator::new(kind)?
+///     .calculate(&ruleset, &score, &beatmap, 0, &difficulty)?;
+/// println!("{:.2}pp", performance.pp());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub enum AnyPerformanceCalculator {
+    Osu(OsuPerformanceCalculator),
+    Taiko(TaikoPerformanceCalculator),
+    Catch(CatchPerformanceCalculator),
+    Mania(ManiaPerformanceCalculator),
+}
+
+impl AnyPerformanceCalculator {
+    /// Creates the performance calculator for `kind`
+    ///
+    /// # Errors
+    /// Returns a [`NativeError`] if osu-native returns an error
+    pub fn new(kind: RulesetKind) -> Result<Self, NativeError> {
+        let calculator = match kind {
+            RulesetKind::Osu => Self::Osu(OsuPerformanceCalculator::new()?),
+            RulesetKind::Taiko => Self::Taiko(TaikoPerformanceCalculator::new()?),
+            RulesetKind::Catch => Self::Catch(CatchPerformanceCalculator::new()?),
+            RulesetKind::Mania => Self::Mania(ManiaPerformanceCalculator::new()?),
+        };
+
+        Ok(calculator)
+    }
+
+    pub fn ruleset(&self) -> RulesetKind {
+        match self {
+            Self::Osu(_) => RulesetKind::Osu,
+            Self::Taiko(_) => RulesetKind::Taiko,
+            Self::Catch(_) => RulesetKind::Catch,
+            Self::Mania(_) => RulesetKind::Mania,
+        }
+    }
+
+    /// Calculates [`AnyPerformanceAttributes`] of a score
+    ///
+    /// # Errors
+    /// Returns an [`AnyPerformanceError::Mismatch`] if `ruleset` or `difficulty_attributes`
+    /// belong to a different ruleset than the calculator
+    /// Returns an [`AnyPerformanceError::Calculation`] if osu-native returns an error
+    pub fn calculate(
+        &self,
+        ruleset: &Ruleset,
+        score: &ScoreStatistics,
+        beatmap: &Beatmap,
+        mods: impl IntoGameMods,
+        difficulty_attributes: &AnyDifficultyAttributes,
+    ) -> Result<AnyPerformanceAttributes, AnyPerformanceError> {
+        let mods = ModCollection::new()
+            .map_err(OsuError::from)?
+            .with_game_mods(mods)
+            .map_err(OsuError::from)?;
+
+        self.calculate_with_collection(ruleset, score, beatmap, &mods, difficulty_attributes)
+    }
+
+    /// Same as [`calculate`](Self::calculate) with an already created native mod
+    /// collection, e.g. to reuse it for many scores
+    ///
+    /// # Errors
+    /// Same as [`calculate`](Self::calculate)
+    pub fn calculate_with_collection(
+        &self,
+        ruleset: &Ruleset,
+        score: &ScoreStatistics,
+        beatmap: &Beatmap,
+        mods: &ModCollection,
+        difficulty_attributes: &AnyDifficultyAttributes,
+    ) -> Result<AnyPerformanceAttributes, AnyPerformanceError> {
+        let expected = self.ruleset();
+
+        if ruleset.kind != expected {
+            return Err(RulesetMismatch {
+                expected,
+                actual: ruleset.kind,
+            }
+            .into());
+        }
+
+        let attributes = match (self, difficulty_attributes) {
+            (Self::Osu(calculator), AnyDifficultyAttributes::Osu(difficulty)) => calculator
+                .calculate_with_collection(ruleset, score, beatmap, mods, difficulty)?
+                .into(),
+            (Self::Taiko(calculator), AnyDifficultyAttributes::Taiko(difficulty)) => calculator
+                .calculate_with_collection(ruleset, score, beatmap, mods, difficulty)?
+                .into(),
+            (Self::Catch(calculator), AnyDifficultyAttributes::Catch(difficulty)) => calculator
+                .calculate_with_collection(ruleset, score, beatmap, mods, difficulty)?
+                .into(),
+            (Self::Mania(calculator), AnyDifficultyAttributes::Mania(difficulty)) => calculator
+                .calculate_with_collection(ruleset, score, beatmap, mods, difficulty)?
+                .into(),
+            (_, difficulty) => {
+                return Err(RulesetMismatch {
+                    expected,
+                    actual: difficulty.ruleset(),
+                }
+                .into());
+            }
+        };
+
+        Ok(attributes)
+    }
+}
+
+/// Performance attributes of any ruleset
+#[derive(Debug, PartialEq)]
+pub enum AnyPerformanceAttributes {
+    Osu(OsuPerformanceAttributes),
+    Taiko(TaikoPerformanceAttributes),
+    Catch(CatchPerformanceAttributes),
+    Mania(ManiaPerformanceAttributes),
+}
+
+impl AnyPerformanceAttributes {
+    pub fn ruleset(&self) -> RulesetKind {
+        match self {
+            Self::Osu(_) => RulesetKind::Osu,
+            Self::Taiko(_) => RulesetKind::Taiko,
+            Self::Catch(_) => RulesetKind::Catch,
+            Self::Mania(_) => RulesetKind::Mania,
+        }
+    }
+
+    pub fn pp(&self) -> f64 {
+        match self {
+            Self::Osu(attributes) => attributes.pp,
+            Self::Taiko(attributes) => attributes.pp,
+            Self::Catch(attributes) => attributes.pp,
+            Self::Mania(attributes) => attributes.pp,
+        }
+    }
+}
+
+macro_rules! impl_any_conversions {
+    ( $( $variant:ident: $attributes:ident, )* ) => {
+        $(
+            impl From<$attributes> for AnyPerformanceAttributes {
+                fn from(attributes: $attributes) -> Self {
+                    Self::$variant(attributes)
+                }
+            }
+
+            impl TryFrom<AnyPerformanceAttributes> for $attributes {
+                type Error = RulesetMismatch;
+
+                fn try_from(attributes: AnyPerformanceAttributes) -> Result<Self, Self::Error> {
+                    match attributes {
+                        AnyPerformanceAttributes::$variant(attributes) => Ok(attributes),
+                        other => Err(RulesetMismatch {
+                            expected: RulesetKind::$variant,
+                            actual: other.ruleset(),
+                        }),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_any_conversions! {
+    Osu: OsuPerformanceAttributes,
+    Taiko: TaikoPerformanceAttributes,
+    Catch: CatchPerformanceAttributes,
+    Mania: ManiaPerformanceAttributes,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnyPerformanceAttributes, AnyPerformanceCalculator, AnyPerformanceError};
+    use crate::{
+        beatmap::Beatmap,
+        difficulty::{
+            any::{AnyDifficultyAttributes, AnyDifficultyCalculator},
+            catch::CatchDifficultyAttributes,
+        },
+        performance::{ScoreStatistics, mania::ManiaPerformanceAttributes},
+        ruleset::{Ruleset, RulesetKind},
+        utils::initialize_path,
+    };
+
+    #[test]
+    fn test_any_performance() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+
+        for kind in [RulesetKind::Osu, RulesetKind::Taiko, RulesetKind::Mania] {
+            let difficulty = AnyDifficultyCalculator::from_kind(kind, &beatmap)
+                .unwrap()
+                .calculate()
+                .unwrap();
+            let score = ScoreStatistics {
+                max_combo: difficulty.max_combo(),
+                ..Default::default()
+            };
+
+            let ruleset = Ruleset::new(kind).unwrap();
+            let performance = AnyPerformanceCalculator::new(kind)
+                .unwrap()
+                .calculate(&ruleset, &score, &beatmap, 0, &difficulty)
+                .unwrap();
+
+            assert_eq!(performance.ruleset(), kind);
+            assert!(performance.pp() > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_any_performance_mismatch() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let calculator = AnyPerformanceCalculator::new(RulesetKind::Osu).unwrap();
+        let osu = Ruleset::new(RulesetKind::Osu).unwrap();
+        let catch = Ruleset::new(RulesetKind::Catch).unwrap();
+        let score = ScoreStatistics::default();
+
+        let difficulty: AnyDifficultyAttributes = CatchDifficultyAttributes {
+            star_rating: 5.0,
+            max_combo: 1000,
+        }
+        .into();
+
+        assert!(matches!(
+            calculator.calculate(&osu, &score, &beatmap, 0, &difficulty),
+            Err(AnyPerformanceError::Mismatch(mismatch)) if mismatch.actual == RulesetKind::Catch
+        ));
+        assert!(matches!(
+            calculator.calculate(&catch, &score, &beatmap, 0, &difficulty),
+            Err(AnyPerformanceError::Mismatch(mismatch)) if mismatch.expected == RulesetKind::Osu
+        ));
+    }
+
+    #[test]
+    fn test_any_performance_attributes_conversions() {
+        let attributes: AnyPerformanceAttributes = ManiaPerformanceAttributes {
+            pp: 420.0,
+            difficulty: 400.0,
+        }
+        .into();
+
+        assert_eq!(attributes.ruleset(), RulesetKind::Mania);
+        assert_eq!(attributes.pp(), 420.0);
+        assert!(ManiaPerformanceAttributes::try_from(attributes).is_ok());
+    }
+}
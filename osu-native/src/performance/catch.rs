@@ -8,7 +8,7 @@ use libosu_native_sys::{
 use crate::{
     beatmap::Beatmap,
     difficulty::catch::CatchDifficultyAttributes,
-    mods::{IntoGameMods, native::ModCollection},
+    mods::native::ModCollection,
     performance::{PerformanceCalculator, ScoreStatistics},
     ruleset::Ruleset,
     utils::HasNative,
@@ -53,17 +53,15 @@ impl PerformanceCalculator for CatchPerformanceCalculator {
     /// Creates an instance of [`CatchPerformanceAttributes`] based on the map and mods provided
     ///
     /// # Errors
-    /// Returns an [`OsuError::ModCollectionError`] if conversion of [`mods`] to [`ModCollection`] fails
     /// Returns an [`OsuError::NativeError`] if osu-native errors
-    fn calculate(
+    fn calculate_with_collection(
         &self,
         ruleset: &Ruleset,
         score: &ScoreStatistics,
         beatmap: &Beatmap,
-        mods: impl IntoGameMods,
+        mods: &ModCollection,
         difficulty_attributes: &Self::DifficultyAttributes,
     ) -> Result<Self::Attributes, crate::error::OsuError> {
-        let mods = ModCollection::new()?.with_game_mods(mods)?;
         let mut attributes = MaybeUninit::uninit();
         let score = NativeScore {
             mods_handle: mods.handle(),
@@ -97,7 +95,8 @@ impl PerformanceCalculator for CatchPerformanceCalculator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchPerformanceAttributes {
     pub pp: f64,
 }
@@ -161,7 +160,7 @@ mod tests {
         let ruleset = Ruleset::new(RulesetKind::Catch).unwrap();
         let perfcalc = CatchPerformanceCalculator::new().unwrap();
         let attributes = perfcalc
-            .calculate(&ruleset, &score, &beatmap, mods.0, &attributes)
+            .calculate(&ruleset, &score, &beatmap, mods, &attributes)
             .unwrap();
 
         assert_ne!(attributes.pp, 0.0);
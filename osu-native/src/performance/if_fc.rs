@@ -0,0 +1,258 @@
+//! Performance a score would have given as a full combo
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{
+//! #     beatmap::Beatmap,
+//! #     difficulty::{DifficultyCalculator, osu::OsuDifficultyCalculator},
+//! #     mods::GameMods,
+//! #     performance::{
+//! #         PerformanceCalculator, ScoreStatistics, if_fc, osu::OsuPerformanceCalculator,
+//! #     },
+//! #     ruleset::{Ruleset, RulesetKind},
+//! # };
+//! # let beatmap = Beatmap::from_path("../../standard.osu")?;
+//! # let ruleset = Ruleset::new(RulesetKind::Osu)?;
+//! let mods = GameMods::default();
+//! let difficulty = OsuDifficultyCalculator::new(ruleset, &beatmap)?.calculate()?;
+//! let score = ScoreStatistics {
+//!     max_combo: 320,
+//!     accuracy: 0.97,
+//!     count_great: 450,
+//!     count_ok: 20,
+//!     count_miss: 4,
+//!     ..Default::default()
+//! };
+//!
+//! let ruleset = Ruleset::new(RulesetKind::Osu)?;
+//! let calculator = OsuPerformanceCalculator::new()?;
+//! let performance =
+//!     if_fc::calculate(&calculator, &ruleset, &beatmap, &score, &mods, &difficulty)?;
+//! println!("{:.2}pp ({:.2}pp if FC)", performance.actual.pp, performance.fc.pp);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{
+    beatmap::Beatmap,
+    difficulty::any::AnyDifficultyAttributes,
+    error::OsuError,
+    mods::GameMods,
+    performance::{
+        PerformanceCalculator, ScoreStatistics,
+        accuracy::{Accuracy, AccuracyMode},
+        simulate::CatchObjectCounts,
+    },
+    ruleset::Ruleset,
+};
+
+/// Performance of a score and of its full combo equivalent
+#[derive(Clone, Debug, PartialEq)]
+pub struct IfFc<A> {
+    pub actual: A,
+    pub fc: A,
+    /// The full combo equivalent of the score
+    pub fc_score: ScoreStatistics,
+}
+
+/// Full combo equivalent of `score` on `beatmap` with the given attributes
+///
+/// Misses become 300s, or 320s in mania, and the combo becomes the beatmap's
+/// max combo. In osu!, all slider tails are hit and no large ticks are missed.
+/// In catch, missed fruits and droplets are caught while missed tiny droplets
+/// stay missed since they don't break combo, the fruits and droplets are
+/// counted with [`CatchObjectCounts::from_beatmap`]. Taiko drumrolls and
+/// swells aren't part of the score so they need no handling.
+///
+/// The accuracy is computed from the new counts, see [`Accuracy`]
+pub fn full_combo(
+    score: &ScoreStatistics,
+    beatmap: &Beatmap,
+    attributes: &AnyDifficultyAttributes,
+    mods: &GameMods,
+) -> ScoreStatistics {
+    let catch = match attributes {
+        AnyDifficultyAttributes::Catch(_) => CatchObjectCounts::from_beatmap(beatmap),
+        _ => None,
+    };
+
+    full_combo_with(score, attributes, mods, catch)
+}
+
+/// [`full_combo`] with the catch objects already counted, if known
+fn full_combo_with(
+    score: &ScoreStatistics,
+    attributes: &AnyDifficultyAttributes,
+    mods: &GameMods,
+    catch: Option<CatchObjectCounts>,
+) -> ScoreStatistics {
+    let mut fc = ScoreStatistics {
+        max_combo: attributes.max_combo(),
+        count_miss: 0,
+        ..score.clone()
+    };
+
+    let mut accuracy = Accuracy::new(attributes.ruleset()).mode(AccuracyMode::from_mods(mods));
+
+    match attributes {
+        AnyDifficultyAttributes::Osu(attributes) => {
+            fc.count_great += score.count_miss;
+            fc.count_slider_tail_hit = attributes.slider_count;
+            fc.count_large_tick_miss = 0;
+            accuracy = accuracy.sliders(attributes.slider_count);
+        }
+        AnyDifficultyAttributes::Taiko(_) => fc.count_great += score.count_miss,
+        AnyDifficultyAttributes::Catch(_) => match catch {
+            Some(counts) => {
+                fc.count_great = counts.fruits;
+                fc.count_ok = counts.droplets;
+            }
+            // Missed droplets can't be told apart from missed fruits
+            None => fc.count_great += score.count_miss,
+        },
+        AnyDifficultyAttributes::Mania(_) => fc.count_perfect += score.count_miss,
+    }
+
+    accuracy.apply(&mut fc);
+
+    fc
+}
+
+/// Calculates the performance of `score` and of its [`full_combo`] equivalent
+///
+/// # Errors
+/// Returns an [`OsuError`] if either calculation fails
+pub fn calculate<C>(
+    calculator: &C,
+    ruleset: &Ruleset,
+    beatmap: &Beatmap,
+    score: &ScoreStatistics,
+    mods: &GameMods,
+    attributes: &C::DifficultyAttributes,
+) -> Result<IfFc<C::Attributes>, OsuError>
+where
+    C: PerformanceCalculator,
+    C::DifficultyAttributes: Clone + Into<AnyDifficultyAttributes>,
+{
+    let fc_score = full_combo(score, beatmap, &attributes.clone().into(), mods);
+
+    Ok(IfFc {
+        actual: calculator.calculate(ruleset, score, beatmap, mods, attributes)?,
+        fc: calculator.calculate(ruleset, &fc_score, beatmap, mods, attributes)?,
+        fc_score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calculate, full_combo_with};
+    use crate::{
+        beatmap::Beatmap,
+        difficulty::{
+            DifficultyCalculator, catch::CatchDifficultyAttributes,
+            mania::ManiaDifficultyAttributes, osu::OsuDifficultyCalculator,
+        },
+        mods::GameMods,
+        performance::{
+            PerformanceCalculator, ScoreStatistics, osu::OsuPerformanceCalculator,
+            simulate::CatchObjectCounts,
+        },
+        ruleset::{Ruleset, RulesetKind},
+        utils::initialize_path,
+    };
+
+    #[test]
+    fn test_mania_full_combo() {
+        let attributes = ManiaDifficultyAttributes {
+            star_rating: 3.0,
+            max_combo: 100,
+        }
+        .into();
+        let score = ScoreStatistics {
+            max_combo: 40,
+            accuracy: 0.9,
+            count_perfect: 80,
+            count_ok: 10,
+            count_miss: 10,
+            ..Default::default()
+        };
+
+        let fc = full_combo_with(&score, &attributes, &GameMods::default(), None);
+
+        assert_eq!(fc.max_combo, 100);
+        assert_eq!(fc.count_perfect, 90);
+        assert_eq!(fc.count_ok, 10);
+        assert_eq!(fc.count_miss, 0);
+        assert_eq!(fc.accuracy, (90.0 * 305.0 + 1000.0) / 30_500.0);
+    }
+
+    #[test]
+    fn test_catch_keeps_tiny_droplet_misses() {
+        let attributes = CatchDifficultyAttributes {
+            star_rating: 4.0,
+            max_combo: 100,
+        }
+        .into();
+        let score = ScoreStatistics {
+            max_combo: 30,
+            count_great: 80,
+            count_ok: 15,
+            count_meh: 40,
+            count_good: 5,
+            count_miss: 5,
+            ..Default::default()
+        };
+
+        // 2 fruits and 3 droplets were missed
+        let counts = CatchObjectCounts {
+            fruits: 82,
+            droplets: 18,
+            tiny_droplets: 45,
+        };
+        let fc = full_combo_with(&score, &attributes, &GameMods::default(), Some(counts));
+
+        assert_eq!((fc.count_great, fc.count_ok), (82, 18));
+        assert_eq!(fc.count_good, 5);
+        assert_eq!(fc.accuracy, 140.0 / 145.0);
+    }
+
+    #[test]
+    fn test_if_fc_osu() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let difficulty = OsuDifficultyCalculator::new(ruleset, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+
+        let score = ScoreStatistics {
+            max_combo: 200,
+            accuracy: 0.95,
+            count_great: difficulty.hit_circle_count + difficulty.slider_count - 30,
+            count_ok: 20,
+            count_miss: 10 + difficulty.spinner_count,
+            count_large_tick_miss: 5,
+            ..Default::default()
+        };
+
+        let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
+        let calculator = OsuPerformanceCalculator::new().unwrap();
+        let performance = calculate(
+            &calculator,
+            &ruleset,
+            &beatmap,
+            &score,
+            &GameMods::default(),
+            &difficulty,
+        )
+        .unwrap();
+
+        assert_eq!(performance.fc_score.max_combo, difficulty.max_combo);
+        assert_eq!(performance.fc_score.count_miss, 0);
+        assert_eq!(performance.fc_score.count_large_tick_miss, 0);
+        assert_eq!(
+            performance.fc_score.count_slider_tail_hit,
+            difficulty.slider_count
+        );
+        assert!(performance.fc.pp > performance.actual.pp);
+    }
+}
@@ -1,28 +1,36 @@
-#![allow(dead_code)]
 use std::{marker::PhantomData, path::Path};
 
-use rosu_mods::GameModSimple;
-
 use crate::{
     beatmap::{Beatmap, BeatmapError},
     difficulty::{
-        DifficultyCalculator, catch::CatchDifficultyCalculator, mania::ManiaDifficultyCalculator,
-        osu::OsuDifficultyCalculator, taiko::TaikoDifficultyCalculator,
+        DifficultyCalculator, any::AnyDifficultyAttributes, catch::CatchDifficultyCalculator,
+        mania::ManiaDifficultyCalculator, osu::OsuDifficultyCalculator,
+        taiko::TaikoDifficultyCalculator,
     },
     error::OsuError,
-    mods::{IntoGameMods, native::ModCollectionError},
+    mods::{GameMods, IntoGameMods, native::ModCollection},
     performance::{
-        catch::CatchPerformanceCalculator, mania::ManiaPerformanceCalculator,
-        osu::OsuPerformanceCalculator, taiko::TaikoPerformanceCalculator,
+        accuracy::{Accuracy, AccuracyMode},
+        catch::CatchPerformanceCalculator,
+        if_fc::IfFc,
+        mania::ManiaPerformanceCalculator,
+        osu::OsuPerformanceCalculator,
+        taiko::TaikoPerformanceCalculator,
+        validation::{ScoreValidator, ValidationMode},
     },
     ruleset::{Ruleset, RulesetError, RulesetKind},
     utils::HasNative,
 };
 
+pub mod accuracy;
+pub mod any;
 pub mod catch;
+pub mod if_fc;
 pub mod mania;
 pub mod osu;
+pub mod simulate;
 pub mod taiko;
+pub mod validation;
 pub trait PerformanceCalculator: Sized {
     type DifficultyAttributes: HasNative;
 
@@ -37,20 +45,52 @@ pub trait PerformanceCalculator: Sized {
         beatmap: &Beatmap,
         mods: impl IntoGameMods,
         difficulty_attributes: &Self::DifficultyAttributes,
+    ) -> Result<Self::Attributes, crate::error::OsuError> {
+        let mods = ModCollection::new()?.with_game_mods(mods)?;
+
+        self.calculate_with_collection(ruleset, score, beatmap, &mods, difficulty_attributes)
+    }
+
+    /// Same as [`calculate`](Self::calculate) with an already created native mod
+    /// collection, e.g. to reuse it for many scores
+    fn calculate_with_collection(
+        &self,
+        ruleset: &Ruleset,
+        score: &ScoreStatistics,
+        beatmap: &Beatmap,
+        mods: &ModCollection,
+        difficulty_attributes: &Self::DifficultyAttributes,
     ) -> Result<Self::Attributes, crate::error::OsuError>;
 }
 
-#[derive(Debug)]
+/// Statistics of a score
+///
+/// With the `serde` feature, counts use the hit result names of lazer's score
+/// statistics, e.g. `great` or `slider_tail_hit`, and missing counts default to 0
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ScoreStatistics {
     pub max_combo: i32,
     pub accuracy: f64,
+    #[cfg_attr(feature = "serde", serde(rename = "miss"))]
     pub count_miss: i32,
-    pub count_meh: i32,     // n50
-    pub count_ok: i32,      // n100
-    pub count_good: i32,    // n200
-    pub count_great: i32,   // n300
+    #[cfg_attr(feature = "serde", serde(rename = "meh"))]
+    pub count_meh: i32, // n50
+    #[cfg_attr(feature = "serde", serde(rename = "ok"))]
+    pub count_ok: i32, // n100
+    #[cfg_attr(feature = "serde", serde(rename = "good"))]
+    pub count_good: i32, // n200
+    #[cfg_attr(feature = "serde", serde(rename = "great"))]
+    pub count_great: i32, // n300
+    #[cfg_attr(feature = "serde", serde(rename = "perfect"))]
     pub count_perfect: i32, // n320
+    #[cfg_attr(feature = "serde", serde(rename = "slider_tail_hit"))]
     pub count_slider_tail_hit: i32,
+    #[cfg_attr(feature = "serde", serde(rename = "large_tick_miss"))]
     pub count_large_tick_miss: i32,
 }
 
@@ -71,22 +111,37 @@ impl Default for ScoreStatistics {
     }
 }
 
-pub struct Empty;
+/// Typestate marker of a [`CalculatorBuilder`] with a beatmap but no ruleset yet
 pub struct WithBeatmap;
 
+/// Ruleset markers for the typestate builders
 pub struct Osu;
 pub struct Taiko;
 pub struct Mania;
 pub struct Catch;
+
+/// Connects a ruleset marker to its calculators
 pub trait RulesetTrait {
     type PerformanceCalculatorTy: PerformanceCalculator;
     type DifficultyCalculatorTy: DifficultyCalculator;
     const KIND: RulesetKind;
+
+    /// Accuracy of scores on a beatmap with the given attributes
+    fn accuracy(_attributes: &DifficultyAttributesOf<Self>) -> Accuracy
+    where
+        Self: Sized,
+    {
+        Accuracy::new(Self::KIND)
+    }
 }
 impl RulesetTrait for Osu {
     type PerformanceCalculatorTy = OsuPerformanceCalculator;
     type DifficultyCalculatorTy = OsuDifficultyCalculator;
     const KIND: RulesetKind = RulesetKind::Osu;
+
+    fn accuracy(attributes: &DifficultyAttributesOf<Self>) -> Accuracy {
+        Accuracy::new(Self::KIND).sliders(attributes.slider_count)
+    }
 }
 impl RulesetTrait for Taiko {
     type PerformanceCalculatorTy = TaikoPerformanceCalculator;
@@ -103,57 +158,96 @@ impl RulesetTrait for Catch {
     type DifficultyCalculatorTy = CatchDifficultyCalculator;
     const KIND: RulesetKind = RulesetKind::Catch;
 }
+
+/// Typestate marker of a [`CalculatorBuilder`] with a beatmap and a ruleset
 pub struct WithRuleset<T: RulesetTrait> {
+    ruleset: Ruleset,
     _marker: PhantomData<T>,
 }
 
+type DifficultyAttributesOf<T> =
+    <<T as RulesetTrait>::PerformanceCalculatorTy as PerformanceCalculator>::DifficultyAttributes;
+type PerformanceAttributesOf<T> =
+    <<T as RulesetTrait>::PerformanceCalculatorTy as PerformanceCalculator>::Attributes;
+
+/// Collects the score of a [`CalculatorBuilder`] and calculates its performance
+///
+/// The max combo defaults to the beatmap's max combo and the accuracy to 100%,
+/// all counts default to 0
 pub struct PerformanceCalculatorBuilder<T: RulesetTrait> {
     beatmap: Beatmap,
     ruleset: Ruleset,
-    mods: Vec<GameModSimple>,
-    difficulty_attributes: <<T as RulesetTrait>::PerformanceCalculatorTy as PerformanceCalculator>::DifficultyAttributes,
+    mods: GameMods,
+    difficulty_attributes: DifficultyAttributesOf<T>,
     score_state: ScoreStatistics,
-    _marker: PhantomData<T>,
+    derive_accuracy: bool,
+    validator: Option<ScoreValidator>,
 }
 
-pub struct CalculatorBuilder<T> {
-    beatmap: Option<Beatmap>,
-    ruleset: Option<Ruleset>,
-    mods: Option<Vec<GameModSimple>>,
-    _marker: PhantomData<T>,
+/// High-level entry point going from a beatmap to difficulty or performance
+///
+/// # Examples
+/// ```no_run
+/// # use osu_native::performance::{CalculatorBuilder, Difficulty};
+/// let performance = CalculatorBuilder::from_path("../../standard.osu")?
+///     .osu()?
+///     .mods(64)?
+///     .performance()?
+///     .max_combo(500)
+///     .n100(12)
+///     .misses(1)
+///     .accuracy(0.98)
+///     .calculate()?;
+/// println!("{:.2}pp", performance.pp);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct CalculatorBuilder<S> {
+    beatmap: Beatmap,
+    mods: GameMods,
+    state: S,
 }
 
 impl CalculatorBuilder<WithBeatmap> {
+    /// Loads the beatmap from a .osu file
+    ///
+    /// # Errors
+    /// See [`Beatmap::from_path`]
     pub fn from_path(map: impl AsRef<Path>) -> Result<Self, BeatmapError> {
-        let beatmap = Beatmap::from_path(map)?;
-        Ok(CalculatorBuilder {
-            beatmap: Some(beatmap),
-            ruleset: None,
-            mods: None,
-            _marker: PhantomData::<WithBeatmap>,
-        })
+        Beatmap::from_path(map).map(Self::from_beatmap)
     }
 
+    /// Loads the beatmap from .osu contents
+    ///
+    /// # Errors
+    /// See [`Beatmap::from_text`]
     pub fn from_text(string: String) -> Result<Self, BeatmapError> {
-        let beatmap = Beatmap::from_text(string)?;
-        Ok(CalculatorBuilder {
-            beatmap: Some(beatmap),
-            ruleset: None,
-            mods: None,
-            _marker: PhantomData::<WithBeatmap>,
-        })
+        Beatmap::from_text(string).map(Self::from_beatmap)
+    }
+
+    pub fn from_beatmap(beatmap: Beatmap) -> Self {
+        CalculatorBuilder {
+            beatmap,
+            mods: GameMods::default(),
+            state: WithBeatmap,
+        }
     }
 }
 
 macro_rules! implement_ruleset {
     ($name:ident, $ty:ident) => {
-        fn $name(self) -> Result<CalculatorBuilder<WithRuleset<$ty>>, RulesetError> {
+        #[doc = concat!("Calculates for the ", stringify!($ty), " ruleset")]
+        ///
+        /// # Errors
+        /// Returns a [`RulesetError`] if the native ruleset can't be created
+        pub fn $name(self) -> Result<CalculatorBuilder<WithRuleset<$ty>>, RulesetError> {
             let ruleset = Ruleset::new($ty::KIND)?;
             Ok(CalculatorBuilder {
                 beatmap: self.beatmap,
-                ruleset: Some(ruleset),
-                mods: None,
-                _marker: PhantomData::<WithRuleset<$ty>>,
+                mods: self.mods,
+                state: WithRuleset {
+                    ruleset,
+                    _marker: PhantomData::<$ty>,
+                },
             })
         }
     };
@@ -170,20 +264,38 @@ impl<T> CalculatorBuilder<WithRuleset<T>>
 where
     T: RulesetTrait,
 {
-    fn mods(
-        mut self,
-        mods: impl IntoGameMods,
-    ) -> Result<CalculatorBuilder<WithRuleset<T>>, ModCollectionError> {
-        self.mods = Some(mods.into_mods()?.0);
+    /// Sets the mods, no mods if never called
+    ///
+    /// # Errors
+    /// Returns an [`OsuError::GameMods`] if the mods can't be converted
+    pub fn mods(mut self, mods: impl IntoGameMods) -> Result<Self, OsuError> {
+        self.mods = mods.into_mods()?;
         Ok(self)
     }
 }
 
-trait Difficulty {
+/// Final steps of a [`CalculatorBuilder`] once the ruleset is known
+pub trait Difficulty {
     type Calculator: DifficultyCalculator;
     type RulesetType: RulesetTrait;
+
+    /// Creates the ruleset's difficulty calculator with the builder's mods
+    ///
+    /// # Errors
+    /// Returns an [`OsuError`] if the calculator can't be created
     fn difficulty(self) -> Result<Self::Calculator, OsuError>;
+
+    /// Calculates difficulty attributes and continues with the score
+    ///
+    /// # Errors
+    /// Returns an [`OsuError`] if the difficulty calculation fails
     fn performance(self) -> Result<PerformanceCalculatorBuilder<Self::RulesetType>, OsuError>;
+
+    /// Continues with the score, using precomputed difficulty attributes
+    fn performance_with_attributes(
+        self,
+        attributes: <Self::Calculator as DifficultyCalculator>::DifficultyAttributes,
+    ) -> PerformanceCalculatorBuilder<Self::RulesetType>;
 }
 
 macro_rules! implement_difficulty {
@@ -193,25 +305,37 @@ macro_rules! implement_difficulty {
             type RulesetType = $ruleset;
 
             fn difficulty(self) -> Result<Self::Calculator, OsuError> {
-                Self::Calculator::new(self.ruleset.unwrap(), self.beatmap.as_ref().unwrap())
+                Self::Calculator::new(self.state.ruleset, &self.beatmap)?.with_mods(self.mods)
             }
+
             fn performance(
                 self,
             ) -> Result<PerformanceCalculatorBuilder<Self::RulesetType>, OsuError> {
-                let ruleset = Ruleset::new(self.ruleset.as_ref().unwrap().kind).unwrap();
-                let attr =
-                    Self::Calculator::new(self.ruleset.unwrap(), self.beatmap.as_ref().unwrap())?
-                        .calculate()?;
-                let mut score = ScoreStatistics::default();
-                score.max_combo = attr.max_combo;
-                Ok(PerformanceCalculatorBuilder {
-                    beatmap: self.beatmap.unwrap(),
-                    ruleset,
-                    mods: self.mods.unwrap(),
-                    difficulty_attributes: attr,
+                let ruleset = Ruleset::new(<$ruleset>::KIND)?;
+                let attributes = Self::Calculator::new(ruleset, &self.beatmap)?
+                    .calculate_with_mods(&self.mods)?;
+
+                Ok(self.performance_with_attributes(attributes))
+            }
+
+            fn performance_with_attributes(
+                self,
+                attributes: <Self::Calculator as DifficultyCalculator>::DifficultyAttributes,
+            ) -> PerformanceCalculatorBuilder<Self::RulesetType> {
+                let score = ScoreStatistics {
+                    max_combo: attributes.max_combo,
+                    ..Default::default()
+                };
+
+                PerformanceCalculatorBuilder {
+                    beatmap: self.beatmap,
+                    ruleset: self.state.ruleset,
+                    mods: self.mods,
+                    difficulty_attributes: attributes,
                     score_state: score,
-                    _marker: PhantomData::<Self::RulesetType>,
-                })
+                    derive_accuracy: false,
+                    validator: None,
+                }
             }
         }
     };
@@ -223,56 +347,139 @@ implement_difficulty!(Mania);
 implement_difficulty!(Catch);
 
 impl<T: RulesetTrait> PerformanceCalculatorBuilder<T> {
-    fn with_score_state(mut self, score: ScoreStatistics) -> Self {
+    pub fn difficulty_attributes(&self) -> &DifficultyAttributesOf<T> {
+        &self.difficulty_attributes
+    }
+
+    pub fn score(&self) -> &ScoreStatistics {
+        &self.score_state
+    }
+
+    /// Replaces the whole score, including max combo and accuracy
+    pub fn with_score_state(mut self, score: ScoreStatistics) -> Self {
         self.score_state = score;
         self
     }
-    fn max_combo(mut self, n: i32) -> Self {
+
+    pub fn max_combo(mut self, n: i32) -> Self {
         self.score_state.max_combo = n;
         self
     }
-    fn misses(mut self, n: i32) -> Self {
+
+    pub fn misses(mut self, n: i32) -> Self {
         self.score_state.count_miss = n;
         self
     }
-    fn accuracy(mut self, acc: f64) -> Self {
+
+    /// Accuracy between 0 and 1
+    pub fn accuracy(mut self, acc: f64) -> Self {
         self.score_state.accuracy = acc;
+        self.derive_accuracy = false;
         self
     }
-    fn calculator(
-        self,
-    ) -> Result<<T::PerformanceCalculatorTy as PerformanceCalculator>::Attributes, OsuError> {
-        let calc = T::PerformanceCalculatorTy::new().unwrap().calculate(
+
+    /// Computes the accuracy from the counts when calculating instead of using
+    /// the set one, see [`Accuracy`]
+    ///
+    /// Lazer osu! accuracy leaves out slider ticks since their number isn't
+    /// part of the difficulty attributes
+    pub fn derive_accuracy(mut self) -> Self {
+        self.derive_accuracy = true;
+        self
+    }
+
+    /// The score that will be calculated, with derived accuracy if enabled
+    pub fn score_statistics(&self) -> ScoreStatistics {
+        let mut score = self.score_state.clone();
+
+        if self.derive_accuracy {
+            T::accuracy(&self.difficulty_attributes)
+                .mode(AccuracyMode::from_mods(&self.mods))
+                .apply(&mut score);
+        }
+
+        score
+    }
+
+    /// Checks the score against the beatmap before calculating, see
+    /// [`ScoreValidator`]
+    pub fn validate(mut self, mode: ValidationMode) -> Self {
+        self.validator = Some(
+            ScoreValidator::new(mode)
+                .mods(&self.mods)
+                .beatmap(&self.beatmap),
+        );
+        self
+    }
+
+    /// Calculates the performance of the current score
+    ///
+    /// # Errors
+    /// Returns an [`OsuError::InvalidScore`] if validation is enabled and the
+    /// score isn't possible on the beatmap
+    /// Returns an [`OsuError`] if the performance calculator can't be created or
+    /// the calculation fails
+    pub fn calculate(&self) -> Result<PerformanceAttributesOf<T>, OsuError>
+    where
+        DifficultyAttributesOf<T>: Clone + Into<AnyDifficultyAttributes>,
+    {
+        let score = self.score_statistics();
+
+        if let Some(ref validator) = self.validator {
+            validator.validate(&score, &self.difficulty_attributes.clone().into())?;
+        }
+
+        T::PerformanceCalculatorTy::new()?.calculate(
+            &self.ruleset,
+            &score,
+            &self.beatmap,
+            &self.mods,
+            &self.difficulty_attributes,
+        )
+    }
+
+    /// Calculates the performance of the current score and of its full combo
+    /// equivalent, see [`if_fc::full_combo`]
+    ///
+    /// # Errors
+    /// Same as [`calculate`](Self::calculate), the full combo isn't validated
+    pub fn calculate_if_fc(&self) -> Result<IfFc<PerformanceAttributesOf<T>>, OsuError>
+    where
+        DifficultyAttributesOf<T>: Clone + Into<AnyDifficultyAttributes>,
+    {
+        let actual = self.calculate()?;
+        let fc_score = if_fc::full_combo(
+            &self.score_statistics(),
+            &self.beatmap,
+            &self.difficulty_attributes.clone().into(),
+            &self.mods,
+        );
+        let fc = T::PerformanceCalculatorTy::new()?.calculate(
             &self.ruleset,
-            &self.score_state,
+            &fc_score,
             &self.beatmap,
-            self.mods,
+            &self.mods,
             &self.difficulty_attributes,
         )?;
-        Ok(calc)
+
+        Ok(IfFc {
+            actual,
+            fc,
+            fc_score,
+        })
     }
 }
 
 macro_rules! implement_setter {
-    // Match patterns like: alias -> field, alias -> field, ...
-    {$($alias:ident -> $field:ident),+ $(,)?} => {
+    {$($(#[$meta:meta])* $alias:ident -> $field:ident),+ $(,)?} => {
         $(
-            fn $alias(mut self, n: i32) -> Self {
+            $(#[$meta])*
+            pub fn $alias(mut self, n: i32) -> Self {
                 self.score_state.$field = n;
                 self
             }
         )+
     };
-
-    // Support for different types, not just i32
-    {$($alias:ident -> $field:ident : $type:ty),+ $(,)?} => {
-        $(
-            fn $alias(mut self, value: $type) -> Self {
-                self.score_state.$field = value;
-                self
-            }
-        )+
-    };
 }
 
 impl PerformanceCalculatorBuilder<Osu> {
@@ -280,10 +487,16 @@ impl PerformanceCalculatorBuilder<Osu> {
         n300 -> count_great,
         n100 -> count_ok,
         n50 -> count_meh,
-        slider_tick_hits -> count_slider_tail_hit,
+        /// Hit slider ends
+        slider_tail_hits -> count_slider_tail_hit,
+        /// Missed slider ticks and repeats
+        large_tick_misses -> count_large_tick_miss,
     }
-    fn slider_tick_misses(mut self, n: i32) -> Self {
-        self.score_state.count_slider_tail_hit = self.difficulty_attributes.slider_count - n;
+
+    /// Sets hit slider ends from the number of missed ones
+    pub fn slider_tail_misses(mut self, n: i32) -> Self {
+        self.score_state.count_slider_tail_hit =
+            (self.difficulty_attributes.slider_count - n).max(0);
         self
     }
 }
@@ -304,9 +517,17 @@ impl PerformanceCalculatorBuilder<Mania> {
         n50 -> count_meh,
     }
 }
+
 impl PerformanceCalculatorBuilder<Catch> {
     implement_setter! {
-        hits -> count_great,
+        /// Caught fruits
+        fruits -> count_great,
+        /// Caught droplets
+        droplets -> count_ok,
+        /// Caught tiny droplets
+        tiny_droplets -> count_meh,
+        /// Missed tiny droplets
+        tiny_droplet_misses -> count_good,
     }
 }
 
@@ -314,7 +535,8 @@ impl PerformanceCalculatorBuilder<Catch> {
 mod tests {
     use crate::{
         difficulty::DifficultyCalculator,
-        performance::{CalculatorBuilder, Difficulty},
+        error::OsuError,
+        performance::{CalculatorBuilder, Difficulty, validation::ValidationMode},
         utils::initialize_path,
     };
 
@@ -334,8 +556,81 @@ mod tests {
             .max_combo(250)
             .n100(10)
             .accuracy(0.98)
-            .calculator()?;
+            .calculate()?;
         assert!(calc.pp > 0.0);
         Ok(())
     }
+
+    #[test]
+    fn test_typestate_without_mods() -> Result<(), Box<dyn std::error::Error>> {
+        let builder = CalculatorBuilder::from_path(initialize_path())?
+            .taiko()?
+            .performance()?;
+        let attributes = builder.difficulty_attributes().clone();
+        assert_eq!(builder.score().max_combo, attributes.max_combo);
+
+        let fc = builder.n300(attributes.max_combo).calculate()?;
+        let precomputed = CalculatorBuilder::from_path(initialize_path())?
+            .taiko()?
+            .performance_with_attributes(attributes.clone())
+            .n300(attributes.max_combo)
+            .calculate()?;
+        assert_eq!(fc.pp, precomputed.pp);
+        Ok(())
+    }
+
+    #[test]
+    fn test_typestate_derive_accuracy() -> Result<(), Box<dyn std::error::Error>> {
+        let builder = CalculatorBuilder::from_path(initialize_path())?
+            .taiko()?
+            .performance()?;
+        let hits = builder.difficulty_attributes().max_combo;
+
+        let builder = builder.n300(hits - 10).misses(10).derive_accuracy();
+        let expected = f64::from(hits - 10) / f64::from(hits);
+        assert_eq!(builder.score_statistics().accuracy, expected);
+        assert_eq!(builder.accuracy(0.5).score_statistics().accuracy, 0.5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_typestate_validate() -> Result<(), Box<dyn std::error::Error>> {
+        let result = CalculatorBuilder::from_path(initialize_path())?
+            .osu()?
+            .performance()?
+            .n300(1)
+            .validate(ValidationMode::Lenient)
+            .calculate();
+        assert!(result.is_ok());
+
+        let result = CalculatorBuilder::from_path(initialize_path())?
+            .osu()?
+            .performance()?
+            .n300(1)
+            .validate(ValidationMode::Strict)
+            .calculate();
+        assert!(matches!(result, Err(OsuError::InvalidScore(_))));
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_score_statistics_serde() {
+        use crate::performance::ScoreStatistics;
+
+        let json = r#"{"max_combo":500,"accuracy":0.97,"great":480,"ok":12,"miss":2}"#;
+        let score: ScoreStatistics = serde_json::from_str(json).unwrap();
+
+        assert_eq!(score.max_combo, 500);
+        assert_eq!(score.count_great, 480);
+        assert_eq!(score.count_ok, 12);
+        assert_eq!(score.count_miss, 2);
+        assert_eq!(score.count_meh, 0);
+
+        let serialized = serde_json::to_string(&score).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ScoreStatistics>(&serialized).unwrap(),
+            score
+        );
+    }
 }
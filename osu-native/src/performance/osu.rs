@@ -8,7 +8,7 @@ use libosu_native_sys::{
 use crate::{
     beatmap::Beatmap,
     difficulty::osu::OsuDifficultyAttributes,
-    mods::{IntoGameMods, native::ModCollection},
+    mods::native::ModCollection,
     performance::{PerformanceCalculator, ScoreStatistics},
     ruleset::Ruleset,
     utils::HasNative,
@@ -44,15 +44,14 @@ impl PerformanceCalculator for OsuPerformanceCalculator {
         Ok(Self { handle })
     }
 
-    fn calculate(
+    fn calculate_with_collection(
         &self,
         ruleset: &Ruleset,
         score: &ScoreStatistics,
         beatmap: &Beatmap,
-        mods: impl IntoGameMods,
+        mods: &ModCollection,
         difficulty_attributes: &Self::DifficultyAttributes,
     ) -> Result<Self::Attributes, crate::error::OsuError> {
-        let mods = ModCollection::new()?.with_game_mods(mods)?;
         let mut attributes = MaybeUninit::uninit();
         let score = NativeScore {
             mods_handle: mods.handle(),
@@ -86,7 +85,8 @@ impl PerformanceCalculator for OsuPerformanceCalculator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuPerformanceAttributes {
     pub pp: f64,
     pub aim: f64,
@@ -170,7 +170,7 @@ mod tests {
         let ruleset = Ruleset::new(RulesetKind::Osu).unwrap();
         let perfcalc = OsuPerformanceCalculator::new().unwrap();
         let attributes = perfcalc
-            .calculate(&ruleset, &score, &beatmap, mods.0, &attributes)
+            .calculate(&ruleset, &score, &beatmap, mods, &attributes)
             .unwrap();
         println!("attributes: {attributes:#?}");
 
@@ -219,13 +219,41 @@ mod tests {
         let perfcalc = OsuPerformanceCalculator::new().expect("Perfcalc creation never fails");
         let mods = calculator.mods();
         let ss_attributes = perfcalc
-            .calculate(&ruleset, &ss, &beatmap, mods.0, &difficulty_attributes)
+            .calculate(&ruleset, &ss, &beatmap, mods, &difficulty_attributes)
             .expect("performance calculations never fail");
-        let mods = calculator.mods();
         let worse_attributes = perfcalc
-            .calculate(&ruleset, &worse, &beatmap, mods.0, &difficulty_attributes)
+            .calculate(&ruleset, &worse, &beatmap, mods, &difficulty_attributes)
             .expect("performance calculations never fail");
 
         assert!(ss_attributes.pp > worse_attributes.pp);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use crate::performance::osu::OsuPerformanceAttributes;
+
+        let attributes = OsuPerformanceAttributes {
+            pp: 727.0,
+            aim: 400.0,
+            speed: 250.0,
+            accuracy: 60.0,
+            flashlight: 0.0,
+            effective_miss_count: 1.5,
+            speed_deviation: None,
+            combo_based_estimated_miss_count: 1.5,
+            score_based_estimated_miss_count: Some(1.0),
+            aim_estimated_slider_breaks: 0.5,
+            speed_estimated_slider_breaks: 0.25,
+        };
+
+        let json = serde_json::to_value(&attributes).unwrap();
+        assert_eq!(json["pp"], 727.0);
+        assert!(json["speed_deviation"].is_null());
+
+        assert_eq!(
+            serde_json::from_value::<OsuPerformanceAttributes>(json).unwrap(),
+            attributes
+        );
+    }
 }
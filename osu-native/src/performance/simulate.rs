@@ -0,0 +1,794 @@
+//! Hit result distributions from a target accuracy, the same way osu-tools'
+//! `simulate` command generates them
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{
+//! #     beatmap::Beatmap,
+//! #     difficulty::{DifficultyCalculator, osu::OsuDifficultyCalculator},
+//! #     performance::simulate::{self, HitResultsTarget, ObjectCounts},
+//! #     ruleset::{Ruleset, RulesetKind},
+//! # };
+//! # let beatmap = Beatmap::from_path("../../standard.osu")?;
+//! # let ruleset = Ruleset::new(RulesetKind::Osu)?;
+//! let attributes = OsuDifficultyCalculator::new(ruleset, &beatmap)?.calculate()?;
+//!
+//! // 98% with 2 misses
+//! let score = simulate::osu(
+//!     &ObjectCounts::from(&attributes),
+//!     &HitResultsTarget::new(0.98, 2),
+//! );
+//! println!("{} / {} / {}", score.count_great, score.count_ok, score.count_meh);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::{
+        Beatmap,
+        decode::{BASE_SCORING_DISTANCE, DecodedBeatmap, HitObjectKind, SliderPath},
+    },
+    difficulty::{
+        any::AnyDifficultyAttributes, osu::OsuDifficultyAttributes,
+        taiko::TaikoDifficultyAttributes,
+    },
+    performance::{
+        ScoreStatistics,
+        accuracy::{Accuracy, AccuracyMode},
+    },
+    ruleset::RulesetKind,
+};
+
+/// Hit object counts of a beatmap
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ObjectCounts {
+    /// Circles, taiko hits or mania notes
+    pub circles: i32,
+    /// Sliders or taiko drumrolls
+    pub sliders: i32,
+    /// Spinners or taiko swells
+    pub spinners: i32,
+    /// Mania hold notes
+    pub holds: i32,
+    /// Max combo of the beatmap, 0 if unknown
+    pub max_combo: i32,
+}
+
+impl ObjectCounts {
+    /// Counts the hit objects of the beatmap's .osu source
+    ///
+    /// The counts are those of the file, so they don't account for conversions
+    /// to another ruleset. The max combo is only known for taiko beatmaps since
+    /// osu! sliders add combo for their ticks and repeats and mania hold notes
+    /// for their duration, use the difficulty attributes' max combo instead
+    pub fn from_beatmap(beatmap: &Beatmap) -> Self {
+        let decoded = beatmap.decoded();
+        let count = |kind| {
+            decoded
+                .hit_objects
+                .iter()
+                .filter(|object| object.kind == kind)
+                .count() as i32
+        };
+
+        let mut counts = Self {
+            circles: count(HitObjectKind::Circle),
+            sliders: count(HitObjectKind::Slider),
+            spinners: count(HitObjectKind::Spinner),
+            holds: count(HitObjectKind::Hold),
+            max_combo: 0,
+        };
+
+        if decoded.mode == RulesetKind::Taiko {
+            counts.max_combo = counts.circles;
+        }
+
+        counts
+    }
+
+    pub fn total(&self) -> i32 {
+        self.circles + self.sliders + self.spinners + self.holds
+    }
+}
+
+impl From<&OsuDifficultyAttributes> for ObjectCounts {
+    fn from(attributes: &OsuDifficultyAttributes) -> Self {
+        Self {
+            circles: attributes.hit_circle_count,
+            sliders: attributes.slider_count,
+            spinners: attributes.spinner_count,
+            holds: 0,
+            max_combo: attributes.max_combo,
+        }
+    }
+}
+
+impl From<&TaikoDifficultyAttributes> for ObjectCounts {
+    /// Only hits are known, which are the only objects that count for taiko
+    /// hit results
+    fn from(attributes: &TaikoDifficultyAttributes) -> Self {
+        Self {
+            circles: attributes.max_combo,
+            max_combo: attributes.max_combo,
+            ..Default::default()
+        }
+    }
+}
+
+/// Catch objects, see [`CatchObjectCounts::from_beatmap`]
+///
+/// Catch difficulty attributes only know fruits and droplets combined as their
+/// max combo, so there is no conversion from them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CatchObjectCounts {
+    /// Fruits, including the ones at the start, repeats and end of juice streams
+    pub fruits: i32,
+    /// Large droplets of juice streams
+    pub droplets: i32,
+    pub tiny_droplets: i32,
+}
+
+impl CatchObjectCounts {
+    /// Generates the objects of the beatmap's .osu source played in osu!catch,
+    /// the same way lazer generates juice streams from sliders
+    ///
+    /// `None` for taiko and mania beatmaps, which can't be converted to catch
+    pub fn from_beatmap(beatmap: &Beatmap) -> Option<Self> {
+        Self::from_decoded(beatmap.decoded())
+    }
+
+    pub(crate) fn from_decoded(beatmap: &DecodedBeatmap) -> Option<Self> {
+        if matches!(beatmap.mode, RulesetKind::Taiko | RulesetKind::Mania) {
+            return None;
+        }
+
+        let mut counts = Self::default();
+
+        for object in &beatmap.hit_objects {
+            match (object.kind, &object.path) {
+                (HitObjectKind::Slider, Some(path)) => {
+                    counts.add_juice_stream(beatmap, object.start_time, path);
+                }
+                // Banana showers don't count towards hit results
+                (HitObjectKind::Spinner, _) => {}
+                _ => counts.fruits += 1,
+            }
+        }
+
+        Some(counts)
+    }
+
+    /// Adds the objects of a juice stream, mirroring lazer's `JuiceStream` and
+    /// `SliderEventGenerator`
+    fn add_juice_stream(&mut self, beatmap: &DecodedBeatmap, start_time: f64, path: &SliderPath) {
+        const MAX_LENGTH: f64 = 100_000.0;
+        const TAIL_LENIENCY: f64 = -36.0;
+
+        let beat_length = beatmap.beat_length_at(start_time);
+        // Stable's precision loss when turning the velocity back into a beat length
+        let bpm_multiplier =
+            f64::from((100.0 / beatmap.slider_velocity_at(start_time)) as f32) / 100.0;
+        let velocity =
+            BASE_SCORING_DISTANCE * beatmap.slider_multiplier / (beat_length * bpm_multiplier);
+        let tick_distance = velocity * beat_length / beatmap.slider_tick_rate;
+
+        let spans = path.slides as i32;
+        let span_duration = path.length / velocity;
+        let length = path.length.min(MAX_LENGTH);
+        let tick_distance = tick_distance.clamp(0.0, length);
+        let min_distance_from_end = velocity * 10.0;
+
+        // Times of the head, ticks, repeats and tail, with whether they're a droplet
+        let mut events = vec![(start_time, false)];
+
+        if tick_distance > 0.0 {
+            for span in 0..spans {
+                let span_start_time = start_time + f64::from(span) * span_duration;
+                let reversed = span % 2 == 1;
+                let mut ticks = Vec::new();
+                let mut distance = tick_distance;
+
+                while distance <= length && distance < length - min_distance_from_end {
+                    let progress = distance / length;
+                    let time_progress = if reversed { 1.0 - progress } else { progress };
+
+                    ticks.push((span_start_time + time_progress * span_duration, true));
+                    distance += tick_distance;
+                }
+
+                if reversed {
+                    ticks.reverse();
+                }
+
+                events.extend(ticks);
+
+                if span < spans - 1 {
+                    events.push((span_start_time + span_duration, false));
+                }
+            }
+        }
+
+        let total_duration = f64::from(spans) * span_duration;
+        let final_span_start_time = start_time + f64::from(spans - 1) * span_duration;
+        let legacy_last_tick = (start_time + total_duration / 2.0)
+            .max(final_span_start_time + span_duration + TAIL_LENIENCY);
+
+        self.fruits += events.iter().filter(|(_, droplet)| !droplet).count() as i32 + 1;
+        self.droplets += events.iter().filter(|(_, droplet)| *droplet).count() as i32;
+
+        // The legacy last tick has no object but tiny droplets lead up to it
+        let times: Vec<_> = events
+            .iter()
+            .map(|(time, _)| *time)
+            .chain([legacy_last_tick, start_time + total_duration])
+            .collect();
+
+        for pair in times.windows(2) {
+            let since_last = f64::from(pair[1] as i32 - pair[0] as i32);
+
+            if since_last <= 80.0 {
+                continue;
+            }
+
+            let mut between = since_last;
+
+            while between > 100.0 {
+                between /= 2.0;
+            }
+
+            let mut time = between;
+
+            while time < since_last {
+                self.tiny_droplets += 1;
+                time += between;
+            }
+        }
+    }
+}
+
+/// The known parts of a score, everything else is derived from the accuracy
+///
+/// Known counts use the [`ScoreStatistics`] fields they end up in
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HitResultsTarget {
+    /// Accuracy between 0 and 1
+    pub accuracy: f64,
+    pub misses: i32,
+    /// The beatmap's max combo if `None`
+    pub combo: Option<i32>,
+    /// Mania 300s
+    pub count_great: Option<i32>,
+    /// Mania 200s
+    pub count_good: Option<i32>,
+    /// 100s, or catch droplets
+    pub count_ok: Option<i32>,
+    /// 50s, or catch tiny droplets
+    pub count_meh: Option<i32>,
+    /// osu! sliders whose end wasn't hit, none by default
+    pub slider_tail_misses: Option<i32>,
+    /// osu! missed slider ticks and repeats, none by default
+    pub large_tick_misses: Option<i32>,
+}
+
+impl HitResultsTarget {
+    pub fn new(accuracy: f64, misses: i32) -> Self {
+        Self {
+            accuracy,
+            misses,
+            ..Default::default()
+        }
+    }
+
+    fn accuracy(&self) -> f64 {
+        if self.accuracy.is_nan() {
+            0.0
+        } else {
+            self.accuracy.clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Closest osu! score to `target`
+///
+/// Without known 100s or 50s, misses are kept and the remaining hits are split
+/// into 300s, 100s and 50s, adding more 50s the lower the accuracy
+pub fn osu(counts: &ObjectCounts, target: &HitResultsTarget) -> ScoreStatistics {
+    let total = counts.total();
+    let mut misses = target.misses.clamp(0, total);
+
+    let (n100, n50) = if target.count_ok.is_some() || target.count_meh.is_some() {
+        let n100 = known(target.count_ok, total - misses);
+        let n50 = known(target.count_meh, total - misses - n100);
+
+        (n100, n50)
+    } else {
+        let relevant = f64::from(total - misses);
+
+        // Pretend there were no misses in the first place, so they stay untouched
+        let accuracy = if relevant > 0.0 {
+            (target.accuracy() * f64::from(total) / relevant).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        if accuracy >= 0.25 {
+            // No 50s at 100%, one per nine 100s at 75% and four per nine at 50%
+            let ratio = (1.0 - (accuracy - 0.25) / 0.75).powi(2);
+            let n100 = 6.0 * relevant * (1.0 - accuracy) / (5.0 * ratio + 4.0);
+            let n50 = n100 * ratio;
+
+            (
+                n100.round() as i32,
+                ((n100 + n50).round() - n100.round()) as i32,
+            )
+        } else if accuracy >= 1.0 / 6.0 {
+            // No 300s, only 100s and 50s
+            let n100 = 6.0 * relevant * accuracy - relevant;
+
+            (n100.round() as i32, (relevant - n100.round()) as i32)
+        } else {
+            // Only 50s, so the accuracy needs more misses
+            let n50 = (6.0 * relevant * accuracy).round() as i32;
+            misses = total - n50;
+
+            (0, n50)
+        }
+    };
+
+    let slider_tail_misses = known(target.slider_tail_misses, counts.sliders);
+
+    let mut score = ScoreStatistics {
+        max_combo: combo(target, counts.max_combo),
+        count_great: total - n100 - n50 - misses,
+        count_ok: n100,
+        count_meh: n50,
+        count_miss: misses,
+        count_slider_tail_hit: counts.sliders - slider_tail_misses,
+        count_large_tick_miss: target.large_tick_misses.unwrap_or(0).max(0),
+        ..Default::default()
+    };
+    classic(RulesetKind::Osu).apply(&mut score);
+
+    score
+}
+
+/// Closest taiko score to `target`, only hits count towards hit results
+pub fn taiko(counts: &ObjectCounts, target: &HitResultsTarget) -> ScoreStatistics {
+    let total = counts.circles;
+    let misses = target.misses.clamp(0, total);
+
+    let n100 = match target.count_ok {
+        Some(n100) => n100.clamp(0, total - misses),
+        None => {
+            // 300 = 2, 100 = 1, miss = 0
+            let target_total = (target.accuracy() * f64::from(total) * 2.0).round() as i32;
+            let n300 = (target_total - (total - misses)).clamp(0, total - misses);
+
+            total - misses - n300
+        }
+    };
+
+    let mut score = ScoreStatistics {
+        max_combo: combo(target, counts.max_combo),
+        count_great: total - n100 - misses,
+        count_ok: n100,
+        count_miss: misses,
+        ..Default::default()
+    };
+    classic(RulesetKind::Taiko).apply(&mut score);
+
+    score
+}
+
+/// Closest catch score to `target`
+///
+/// Misses are taken from droplets first, then fruits. Tiny droplets aren't
+/// part of the misses and are derived from the accuracy instead
+pub fn catch(counts: &CatchObjectCounts, target: &HitResultsTarget) -> ScoreStatistics {
+    let max_combo = counts.fruits + counts.droplets;
+    let misses = target.misses.clamp(0, max_combo);
+
+    let droplets = match target.count_ok {
+        Some(droplets) => droplets.clamp(0, counts.droplets),
+        None => (counts.droplets - misses).max(0),
+    };
+    let fruits = (counts.fruits - (misses - (counts.droplets - droplets))).clamp(0, counts.fruits);
+
+    let tiny_droplets = match target.count_meh {
+        Some(tiny_droplets) => tiny_droplets,
+        None => {
+            let hits = target.accuracy() * f64::from(max_combo + counts.tiny_droplets);
+
+            hits.round() as i32 - fruits - droplets
+        }
+    }
+    .clamp(0, counts.tiny_droplets);
+
+    let mut score = ScoreStatistics {
+        max_combo: combo(target, max_combo),
+        count_great: fruits,
+        count_ok: droplets,
+        count_meh: tiny_droplets,
+        count_good: counts.tiny_droplets - tiny_droplets,
+        count_miss: max_combo - fruits - droplets,
+        ..Default::default()
+    };
+    classic(RulesetKind::Catch).apply(&mut score);
+
+    score
+}
+
+/// Closest mania score to `target`
+///
+/// Hold notes count twice, once for their head and once for their tail. The
+/// 320s and 300s are equally weighted for the generated accuracy, so like
+/// osu-tools the derived ones are split evenly between them
+pub fn mania(counts: &ObjectCounts, target: &HitResultsTarget) -> ScoreStatistics {
+    let total = counts.total() + counts.holds;
+    let misses = target.misses.clamp(0, total);
+    let remaining = total - misses;
+
+    let known_counts = [
+        target.count_great,
+        target.count_good,
+        target.count_ok,
+        target.count_meh,
+    ];
+
+    let [n300, n200, n100, n50] = if known_counts.iter().any(Option::is_some) {
+        let mut left = remaining;
+
+        known_counts.map(|count| {
+            let count = known(count, left);
+            left -= count;
+
+            count
+        })
+    } else {
+        // 320 = 300 = 6, 200 = 4, 100 = 2, 50 = 1, miss = 0
+        let target_total = (target.accuracy() * f64::from(total) * 6.0).round() as i32;
+
+        // Starting with only 50s, this is how much the rest needs to add
+        let mut delta = (target_total - remaining).max(0);
+        let mut left = remaining;
+
+        // The odd one out of the 320s and 300s is a 320
+        let n320_and_n300 = (delta / 5).min(left);
+        delta -= n320_and_n300 * 5;
+        left -= n320_and_n300;
+        let n200 = (delta / 3).min(left);
+        delta -= n200 * 3;
+        left -= n200;
+        let n100 = delta.min(left);
+        left -= n100;
+
+        [n320_and_n300 / 2, n200, n100, left]
+    };
+
+    let mut score = ScoreStatistics {
+        max_combo: combo(target, counts.max_combo),
+        count_perfect: remaining - n300 - n200 - n100 - n50,
+        count_great: n300,
+        count_good: n200,
+        count_ok: n100,
+        count_meh: n50,
+        count_miss: misses,
+        ..Default::default()
+    };
+    classic(RulesetKind::Mania).apply(&mut score);
+
+    score
+}
+
+/// The objects of a beatmap can't be counted in the ruleset of the attributes,
+/// see [`any`]
+#[derive(Debug, ThisError)]
+#[error("Objects of {beatmap:?} beatmaps can't be counted in {ruleset:?}")]
+pub struct UnknownCounts {
+    /// Ruleset of the beatmap's .osu source
+    pub beatmap: RulesetKind,
+    pub ruleset: RulesetKind,
+}
+
+/// Closest score to `target` for `beatmap` and its difficulty attributes of
+/// any ruleset
+///
+/// osu! and taiko objects are counted by the attributes. Catch objects are
+/// generated with [`CatchObjectCounts::from_beatmap`] and mania objects are
+/// counted with [`ObjectCounts::from_beatmap`], mods that add or remove objects
+/// aren't taken into account. The notes of beatmaps converted to mania are
+/// unknown, so there the max combo is used as the number of hit results
+///
+/// # Errors
+/// Returns [`UnknownCounts`] for catch attributes of a beatmap that can't be
+/// converted to catch
+pub fn any(
+    beatmap: &Beatmap,
+    attributes: &AnyDifficultyAttributes,
+    target: &HitResultsTarget,
+) -> Result<ScoreStatistics, UnknownCounts> {
+    let unknown = |ruleset| UnknownCounts {
+        beatmap: beatmap.decoded().mode,
+        ruleset,
+    };
+
+    match attributes {
+        AnyDifficultyAttributes::Osu(attributes) => Ok(osu(&attributes.into(), target)),
+        AnyDifficultyAttributes::Taiko(attributes) => Ok(taiko(&attributes.into(), target)),
+        AnyDifficultyAttributes::Catch(_) => {
+            let counts = CatchObjectCounts::from_beatmap(beatmap)
+                .ok_or_else(|| unknown(RulesetKind::Catch))?;
+
+            Ok(catch(&counts, target))
+        }
+        AnyDifficultyAttributes::Mania(attributes) => {
+            let counts = if beatmap.decoded().mode == RulesetKind::Mania {
+                ObjectCounts::from_beatmap(beatmap)
+            } else {
+                ObjectCounts {
+                    circles: attributes.max_combo,
+                    ..Default::default()
+                }
+            };
+
+            Ok(mania(
+                &ObjectCounts {
+                    max_combo: attributes.max_combo,
+                    ..counts
+                },
+                target,
+            ))
+        }
+    }
+}
+
+/// A known count, limited to what's left
+fn known(count: Option<i32>, left: i32) -> i32 {
+    count.unwrap_or(0).clamp(0, left.max(0))
+}
+
+/// The target's combo, limited to the max combo if it's known
+fn combo(target: &HitResultsTarget, max_combo: i32) -> i32 {
+    match target.combo {
+        Some(combo) if max_combo > 0 => combo.clamp(0, max_combo),
+        Some(combo) => combo.max(0),
+        None => max_combo,
+    }
+}
+
+/// The weights used to generate the counts
+fn classic(ruleset: RulesetKind) -> Accuracy {
+    Accuracy::new(ruleset).mode(AccuracyMode::Classic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CatchObjectCounts, HitResultsTarget, ObjectCounts, UnknownCounts, any, catch, mania, osu,
+        taiko,
+    };
+    use crate::{
+        beatmap::{Beatmap, builder::BeatmapBuilder, decode::DecodedBeatmap},
+        difficulty::{catch::CatchDifficultyAttributes, mania::ManiaDifficultyAttributes},
+        ruleset::RulesetKind,
+        utils::initialize_path,
+    };
+
+    const OSU: ObjectCounts = ObjectCounts {
+        circles: 300,
+        sliders: 180,
+        spinners: 2,
+        holds: 0,
+        max_combo: 700,
+    };
+
+    #[test]
+    fn test_osu_from_accuracy() {
+        let score = osu(&OSU, &HitResultsTarget::new(0.98, 2));
+
+        assert_eq!(score.count_miss, 2);
+        assert_eq!(
+            score.count_great + score.count_ok + score.count_meh + score.count_miss,
+            OSU.total()
+        );
+        assert!((score.accuracy - 0.98).abs() < 0.001);
+        assert!(score.count_ok > score.count_meh);
+        assert_eq!(score.count_slider_tail_hit, OSU.sliders);
+        assert_eq!(score.max_combo, OSU.max_combo);
+
+        let perfect = osu(&OSU, &HitResultsTarget::new(1.0, 0));
+        assert_eq!(perfect.count_great, OSU.total());
+        assert_eq!(perfect.accuracy, 1.0);
+    }
+
+    #[test]
+    fn test_osu_known_counts() {
+        let target = HitResultsTarget {
+            count_ok: Some(10),
+            count_meh: Some(1000),
+            slider_tail_misses: Some(5),
+            combo: Some(10_000),
+            ..HitResultsTarget::new(0.5, 3)
+        };
+        let score = osu(&OSU, &target);
+
+        assert_eq!(score.count_ok, 10);
+        // Limited to the objects left
+        assert_eq!(score.count_meh, OSU.total() - 13);
+        assert_eq!(score.count_great, 0);
+        assert_eq!(score.count_slider_tail_hit, OSU.sliders - 5);
+        assert_eq!(score.max_combo, OSU.max_combo);
+    }
+
+    #[test]
+    fn test_osu_low_accuracy_adds_misses() {
+        let score = osu(&OSU, &HitResultsTarget::new(0.1, 0));
+
+        assert_eq!(score.count_great, 0);
+        assert_eq!(score.count_ok, 0);
+        assert!(score.count_miss > 0);
+        assert!((score.accuracy - 0.1).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_taiko_from_accuracy() {
+        let counts = ObjectCounts {
+            circles: 1000,
+            max_combo: 1000,
+            ..Default::default()
+        };
+        let score = taiko(&counts, &HitResultsTarget::new(0.97, 5));
+
+        assert_eq!(score.count_great + score.count_ok + score.count_miss, 1000);
+        assert_eq!(score.count_miss, 5);
+        assert!((score.accuracy - 0.97).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_catch_from_accuracy() {
+        let counts = CatchObjectCounts {
+            fruits: 500,
+            droplets: 100,
+            tiny_droplets: 400,
+        };
+        let score = catch(&counts, &HitResultsTarget::new(0.98, 3));
+
+        assert_eq!(score.count_great, 500);
+        assert_eq!(score.count_ok, 97);
+        assert_eq!(score.count_miss, 3);
+        assert_eq!(score.count_meh + score.count_good, 400);
+        assert_eq!(score.max_combo, 600);
+        assert!((score.accuracy - 0.98).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_mania_from_accuracy() {
+        let counts = ObjectCounts {
+            circles: 800,
+            holds: 100,
+            max_combo: 1000,
+            ..Default::default()
+        };
+        let score = mania(&counts, &HitResultsTarget::new(0.95, 10));
+        let hits = score.count_perfect
+            + score.count_great
+            + score.count_good
+            + score.count_ok
+            + score.count_meh;
+
+        assert_eq!(hits + score.count_miss, 1000);
+        assert!(score.count_great > 0);
+        assert!((0..=1).contains(&(score.count_perfect - score.count_great)));
+        assert!((score.accuracy - 0.95).abs() < 0.001);
+
+        let odd = ObjectCounts {
+            circles: 801,
+            ..counts
+        };
+        let ss = mania(&odd, &HitResultsTarget::new(1.0, 0));
+        assert_eq!((ss.count_perfect, ss.count_great), (501, 500));
+        assert_eq!(ss.accuracy, 1.0);
+
+        let known = mania(
+            &counts,
+            &HitResultsTarget {
+                count_great: Some(50),
+                ..HitResultsTarget::new(0.95, 10)
+            },
+        );
+        assert_eq!(known.count_great, 50);
+        assert_eq!(known.count_perfect, 940);
+    }
+
+    #[test]
+    fn test_catch_object_counts() {
+        // 0.25px/ms with a tick every 100px, so 400ms between ticks
+        let builder = BeatmapBuilder::new(RulesetKind::Catch)
+            .slider_multiplier(1.0)
+            .timing_point(0.0, 150.0)
+            .slider(0.0, (100.0, 100.0), &[(400.0, 100.0)], 1, 300.0)
+            .slider(2000.0, (100.0, 100.0), &[(400.0, 100.0)], 2, 300.0)
+            .circle(5000.0, (256.0, 192.0))
+            .spinner(6000.0, 7000.0);
+        let counts =
+            CatchObjectCounts::from_decoded(&DecodedBeatmap::from_text(&builder.to_osu_string()));
+
+        // Three tiny droplets between ticks, none in the last 36ms
+        assert_eq!(
+            counts,
+            Some(CatchObjectCounts {
+                fruits: 2 + 3 + 1,
+                droplets: 2 + 4,
+                tiny_droplets: 9 + 18,
+            })
+        );
+
+        let taiko = BeatmapBuilder::new(RulesetKind::Taiko).circle(0.0, (256.0, 192.0));
+        assert_eq!(
+            CatchObjectCounts::from_decoded(&DecodedBeatmap::from_text(&taiko.to_osu_string())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_any_counts_objects_of_beatmap() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let counts = CatchObjectCounts::from_beatmap(&beatmap).unwrap();
+        let target = HitResultsTarget::new(0.97, 4);
+
+        let attributes = CatchDifficultyAttributes {
+            star_rating: 5.0,
+            max_combo: counts.fruits + counts.droplets,
+        }
+        .into();
+        let score = any(&beatmap, &attributes, &target).unwrap();
+
+        assert_eq!(score, catch(&counts, &target));
+        assert!((score.accuracy - 0.97).abs() < 0.001);
+
+        // The notes of a converted beatmap depend on the key count
+        let attributes = ManiaDifficultyAttributes {
+            star_rating: 5.0,
+            max_combo: 1000,
+        }
+        .into();
+        let score = any(&beatmap, &attributes, &target).unwrap();
+        let hits = score.count_perfect
+            + score.count_great
+            + score.count_good
+            + score.count_ok
+            + score.count_meh;
+
+        assert_eq!(hits + score.count_miss, 1000);
+
+        let taiko = BeatmapBuilder::new(RulesetKind::Taiko)
+            .circle(0.0, (256.0, 192.0))
+            .build()
+            .unwrap();
+        let attributes = CatchDifficultyAttributes {
+            star_rating: 1.0,
+            max_combo: 1,
+        }
+        .into();
+
+        assert!(matches!(
+            any(&taiko, &attributes, &target),
+            Err(UnknownCounts {
+                beatmap: RulesetKind::Taiko,
+                ruleset: RulesetKind::Catch,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_object_counts_from_beatmap() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let counts = ObjectCounts::from_beatmap(&beatmap);
+
+        assert!(counts.circles > 0);
+        assert!(counts.sliders > 0);
+        assert_eq!(counts.holds, 0);
+        assert_eq!(counts.max_combo, 0);
+    }
+}
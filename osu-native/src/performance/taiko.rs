@@ -8,7 +8,7 @@ use libosu_native_sys::{
 use crate::{
     beatmap::Beatmap,
     difficulty::taiko::TaikoDifficultyAttributes,
-    mods::{IntoGameMods, native::ModCollection},
+    mods::native::ModCollection,
     performance::{PerformanceCalculator, ScoreStatistics},
     ruleset::Ruleset,
     utils::HasNative,
@@ -44,15 +44,14 @@ impl PerformanceCalculator for TaikoPerformanceCalculator {
         Ok(Self { handle })
     }
 
-    fn calculate(
+    fn calculate_with_collection(
         &self,
         ruleset: &Ruleset,
         score: &ScoreStatistics,
         beatmap: &Beatmap,
-        mods: impl IntoGameMods,
+        mods: &ModCollection,
         difficulty_attributes: &Self::DifficultyAttributes,
     ) -> Result<Self::Attributes, crate::error::OsuError> {
-        let mods = ModCollection::new()?.with_game_mods(mods)?;
         let mut attributes = MaybeUninit::uninit();
         let score = NativeScore {
             mods_handle: mods.handle(),
@@ -86,7 +85,8 @@ impl PerformanceCalculator for TaikoPerformanceCalculator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoPerformanceAttributes {
     pub pp: f64,
     pub difficulty: f64,
@@ -156,7 +156,7 @@ mod tests {
         let ruleset = Ruleset::new(RulesetKind::Taiko).unwrap();
         let perfcalc = TaikoPerformanceCalculator::new().unwrap();
         let attributes = perfcalc
-            .calculate(&ruleset, &score, &beatmap, mods.0, &attributes)
+            .calculate(&ruleset, &score, &beatmap, mods, &attributes)
             .unwrap();
         println!("attributes: {attributes:#?}");
 
@@ -0,0 +1,470 @@
+//! Checks that a score is possible on a beatmap before calculating its performance
+//!
+//! osu-native calculates performance for any [`ScoreStatistics`], even ones
+//! whose counts don't match the beatmap
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{
+//! #     beatmap::Beatmap,
+//! #     difficulty::any::AnyDifficultyCalculator,
+//! #     performance::{
+//! #         ScoreStatistics,
+//! #         validation::{ScoreValidator, ValidationMode},
+//! #     },
+//! #     ruleset::RulesetKind,
+//! # };
+//! # let beatmap = Beatmap::from_path("../../standard.osu")?;
+//! let difficulty = AnyDifficultyCalculator::from_kind(RulesetKind::Osu, &beatmap)?.calculate()?;
+//! let score = ScoreStatistics {
+//!     max_combo: 10_000,
+//!     count_great: 3,
+//!     ..Default::default()
+//! };
+//!
+//! for violation in ScoreValidator::new(ValidationMode::Strict).violations(&score, &difficulty) {
+//!     println!("{violation}");
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::Beatmap,
+    difficulty::any::AnyDifficultyAttributes,
+    mods::GameMods,
+    performance::{
+        ScoreStatistics,
+        accuracy::{Accuracy, AccuracyError, AccuracyMode},
+        simulate::ObjectCounts,
+    },
+    ruleset::RulesetKind,
+};
+
+/// A reason why a score isn't possible on a beatmap
+#[derive(Clone, Debug, PartialEq, ThisError)]
+pub enum ScoreViolation {
+    #[error("`{field}` is negative ({count})")]
+    NegativeCount { field: &'static str, count: i32 },
+    #[error("Hit results add up to {actual} but the beatmap has {expected} objects")]
+    HitCountMismatch { expected: i32, actual: i32 },
+    #[error("Combo {combo} exceeds the beatmap's max combo {max_combo}")]
+    ComboTooHigh { combo: i32, max_combo: i32 },
+    #[error("`{field}` isn't used in {ruleset:?} but is {count}")]
+    IrrelevantCount {
+        ruleset: RulesetKind,
+        field: &'static str,
+        count: i32,
+    },
+    #[error("{count} slider tails were hit but the beatmap has {sliders} sliders")]
+    TooManySliderTails { count: i32, sliders: i32 },
+    #[error("Accuracy {0} isn't between 0 and 1")]
+    AccuracyOutOfRange(f64),
+    #[error("Accuracy {supplied} doesn't match the counts' accuracy {computed}")]
+    AccuracyMismatch { supplied: f64, computed: f64 },
+}
+
+/// All violations of a score that failed validation
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvalidScore(pub Vec<ScoreViolation>);
+
+impl Display for InvalidScore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Invalid score")?;
+
+        for (i, violation) in self.0.iter().enumerate() {
+            let separator = if i == 0 { ": " } else { ", " };
+            write!(f, "{separator}{violation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidScore {}
+
+/// How strictly a score has to match the beatmap
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Hit results have to add up to the object count and a supplied accuracy
+    /// has to match the counts
+    #[default]
+    Strict,
+    /// Allows fewer hit results than objects, e.g. for failed plays, and any
+    /// accuracy between 0 and 1
+    Lenient,
+}
+
+/// Compares [`ScoreStatistics`] against difficulty attributes
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScoreValidator {
+    mode: ValidationMode,
+    accuracy: AccuracyMode,
+    mania_hit_results: Option<i32>,
+}
+
+impl ScoreValidator {
+    pub fn new(mode: ValidationMode) -> Self {
+        Self {
+            mode,
+            accuracy: AccuracyMode::default(),
+            mania_hit_results: None,
+        }
+    }
+
+    /// Uses the accuracy mode of the mods for the accuracy check
+    pub fn mods(mut self, mods: &GameMods) -> Self {
+        self.accuracy = AccuracyMode::from_mods(mods);
+        self
+    }
+
+    /// Counts mania hit results from the notes of the beatmap, see [`ObjectCounts::from_beatmap`]
+    ///
+    /// Only applies to mania beatmaps, the notes of converted beatmaps are unknown
+    pub fn beatmap(mut self, beatmap: &Beatmap) -> Self {
+        self.mania_hit_results = (beatmap.decoded().mode == RulesetKind::Mania).then(|| {
+            let counts = ObjectCounts::from_beatmap(beatmap);
+
+            counts.total() + counts.holds
+        });
+        self
+    }
+
+    /// All violations of the score, empty if it's valid
+    ///
+    /// Lazer osu! accuracy depends on the beatmap's slider ticks which aren't
+    /// part of the difficulty attributes, so it's only checked with the Classic mod.
+    /// Mania hit results count notes and both ends of hold notes, which isn't
+    /// the max combo, so they're only checked with a [`beatmap`](Self::beatmap)
+    pub fn violations(
+        &self,
+        score: &ScoreStatistics,
+        attributes: &AnyDifficultyAttributes,
+    ) -> Vec<ScoreViolation> {
+        let ruleset = attributes.ruleset();
+        let mut violations = Vec::new();
+
+        for (field, count) in fields(score) {
+            if count < 0 {
+                violations.push(ScoreViolation::NegativeCount { field, count });
+            }
+        }
+
+        // Catch's max combo is the number of fruits and droplets
+        let hit_counts = match attributes {
+            AnyDifficultyAttributes::Osu(attributes) => Some((
+                attributes.hit_circle_count + attributes.slider_count + attributes.spinner_count,
+                score.count_great + score.count_ok + score.count_meh + score.count_miss,
+            )),
+            AnyDifficultyAttributes::Taiko(attributes) => Some((
+                attributes.max_combo,
+                score.count_great + score.count_ok + score.count_miss,
+            )),
+            AnyDifficultyAttributes::Catch(attributes) => Some((
+                attributes.max_combo,
+                score.count_great + score.count_ok + score.count_miss,
+            )),
+            AnyDifficultyAttributes::Mania(_) => self.mania_hit_results.map(|expected| {
+                (
+                    expected,
+                    score.count_perfect
+                        + score.count_great
+                        + score.count_good
+                        + score.count_ok
+                        + score.count_meh
+                        + score.count_miss,
+                )
+            }),
+        };
+
+        if let Some((expected, actual)) = hit_counts {
+            let partial = self.mode == ValidationMode::Lenient && actual < expected;
+
+            if actual != expected && !partial {
+                violations.push(ScoreViolation::HitCountMismatch { expected, actual });
+            }
+        }
+
+        let max_combo = attributes.max_combo();
+
+        if score.max_combo > max_combo {
+            violations.push(ScoreViolation::ComboTooHigh {
+                combo: score.max_combo,
+                max_combo,
+            });
+        }
+
+        for (field, count) in fields(score) {
+            if count != 0 && irrelevant(ruleset).contains(&field) {
+                violations.push(ScoreViolation::IrrelevantCount {
+                    ruleset,
+                    field,
+                    count,
+                });
+            }
+        }
+
+        if let AnyDifficultyAttributes::Osu(attributes) = attributes
+            && score.count_slider_tail_hit > attributes.slider_count
+        {
+            violations.push(ScoreViolation::TooManySliderTails {
+                count: score.count_slider_tail_hit,
+                sliders: attributes.slider_count,
+            });
+        }
+
+        if !(0.0..=1.0).contains(&score.accuracy) {
+            violations.push(ScoreViolation::AccuracyOutOfRange(score.accuracy));
+        } else if self.mode == ValidationMode::Strict && self.checks_accuracy(ruleset) {
+            let accuracy = match attributes {
+                AnyDifficultyAttributes::Osu(attributes) => {
+                    Accuracy::new(ruleset).sliders(attributes.slider_count)
+                }
+                _ => Accuracy::new(ruleset),
+            };
+
+            if let Err(AccuracyError::Mismatch { supplied, computed }) =
+                accuracy.mode(self.accuracy).validate(score)
+            {
+                violations.push(ScoreViolation::AccuracyMismatch { supplied, computed });
+            }
+        }
+
+        violations
+    }
+
+    /// Checks the score
+    ///
+    /// # Errors
+    /// Returns an [`InvalidScore`] with all [`violations`](Self::violations) if
+    /// there are any
+    pub fn validate(
+        &self,
+        score: &ScoreStatistics,
+        attributes: &AnyDifficultyAttributes,
+    ) -> Result<(), InvalidScore> {
+        let violations = self.violations(score, attributes);
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidScore(violations))
+        }
+    }
+
+    fn checks_accuracy(&self, ruleset: RulesetKind) -> bool {
+        ruleset != RulesetKind::Osu || self.accuracy == AccuracyMode::Classic
+    }
+}
+
+/// Combo and counts of the score by field name
+fn fields(score: &ScoreStatistics) -> [(&'static str, i32); 9] {
+    [
+        ("max_combo", score.max_combo),
+        ("count_miss", score.count_miss),
+        ("count_meh", score.count_meh),
+        ("count_ok", score.count_ok),
+        ("count_good", score.count_good),
+        ("count_great", score.count_great),
+        ("count_perfect", score.count_perfect),
+        ("count_slider_tail_hit", score.count_slider_tail_hit),
+        ("count_large_tick_miss", score.count_large_tick_miss),
+    ]
+}
+
+/// Fields that are always 0 in the ruleset
+fn irrelevant(ruleset: RulesetKind) -> &'static [&'static str] {
+    match ruleset {
+        RulesetKind::Osu => &["count_good", "count_perfect"],
+        RulesetKind::Taiko => &[
+            "count_meh",
+            "count_good",
+            "count_perfect",
+            "count_slider_tail_hit",
+            "count_large_tick_miss",
+        ],
+        RulesetKind::Catch => &[
+            "count_perfect",
+            "count_slider_tail_hit",
+            "count_large_tick_miss",
+        ],
+        RulesetKind::Mania => &["count_slider_tail_hit", "count_large_tick_miss"],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InvalidScore, ScoreValidator, ScoreViolation, ValidationMode};
+    use crate::{
+        beatmap::{Beatmap, builder::BeatmapBuilder},
+        difficulty::{
+            any::{AnyDifficultyAttributes, AnyDifficultyCalculator},
+            catch::CatchDifficultyAttributes,
+            mania::ManiaDifficultyAttributes,
+        },
+        performance::ScoreStatistics,
+        ruleset::RulesetKind,
+        utils::initialize_path,
+    };
+
+    fn mania() -> AnyDifficultyAttributes {
+        ManiaDifficultyAttributes {
+            star_rating: 3.0,
+            max_combo: 100,
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_valid_score() {
+        let score = ScoreStatistics {
+            max_combo: 100,
+            count_perfect: 100,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ScoreValidator::new(ValidationMode::Strict).validate(&score, &mania()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_strict_and_lenient() {
+        let catch = CatchDifficultyAttributes {
+            star_rating: 4.0,
+            max_combo: 100,
+        }
+        .into();
+        let score = ScoreStatistics {
+            max_combo: 50,
+            accuracy: 0.9,
+            count_great: 50,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ScoreValidator::new(ValidationMode::Strict).violations(&score, &catch),
+            [
+                ScoreViolation::HitCountMismatch {
+                    expected: 100,
+                    actual: 50
+                },
+                ScoreViolation::AccuracyMismatch {
+                    supplied: 0.9,
+                    computed: 1.0
+                },
+            ]
+        );
+        assert!(
+            ScoreValidator::new(ValidationMode::Lenient)
+                .violations(&score, &catch)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_mania_hit_results() {
+        let beatmap = BeatmapBuilder::new(RulesetKind::Mania)
+            .note(0.0, 0)
+            .hold(500.0, 1, 1000.0)
+            .build()
+            .unwrap();
+        let score = ScoreStatistics {
+            max_combo: 100,
+            count_perfect: 100,
+            ..Default::default()
+        };
+
+        // Without the beatmap there is nothing to compare to
+        assert!(
+            ScoreValidator::new(ValidationMode::Strict)
+                .violations(&score, &mania())
+                .is_empty()
+        );
+        assert_eq!(
+            ScoreValidator::new(ValidationMode::Strict)
+                .beatmap(&beatmap)
+                .violations(&score, &mania()),
+            [ScoreViolation::HitCountMismatch {
+                expected: 3,
+                actual: 100
+            }]
+        );
+    }
+
+    #[test]
+    fn test_always_checked_violations() {
+        let catch = CatchDifficultyAttributes {
+            star_rating: 4.0,
+            max_combo: 300,
+        }
+        .into();
+        let score = ScoreStatistics {
+            max_combo: 400,
+            accuracy: 1.5,
+            count_great: 310,
+            count_miss: -10,
+            count_perfect: 3,
+            ..Default::default()
+        };
+
+        let Err(InvalidScore(violations)) =
+            ScoreValidator::new(ValidationMode::Lenient).validate(&score, &catch)
+        else {
+            panic!("score should be invalid");
+        };
+
+        assert_eq!(
+            violations,
+            [
+                ScoreViolation::NegativeCount {
+                    field: "count_miss",
+                    count: -10
+                },
+                ScoreViolation::ComboTooHigh {
+                    combo: 400,
+                    max_combo: 300
+                },
+                ScoreViolation::IrrelevantCount {
+                    ruleset: RulesetKind::Catch,
+                    field: "count_perfect",
+                    count: 3
+                },
+                ScoreViolation::AccuracyOutOfRange(1.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_osu_object_count() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let difficulty = AnyDifficultyCalculator::from_kind(RulesetKind::Osu, &beatmap)
+            .unwrap()
+            .calculate()
+            .unwrap();
+        let AnyDifficultyAttributes::Osu(ref attributes) = difficulty else {
+            unreachable!()
+        };
+
+        let score = ScoreStatistics {
+            max_combo: attributes.max_combo,
+            count_great: attributes.hit_circle_count
+                + attributes.slider_count
+                + attributes.spinner_count,
+            count_slider_tail_hit: attributes.slider_count + 1,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ScoreValidator::new(ValidationMode::Strict).violations(&score, &difficulty),
+            [ScoreViolation::TooManySliderTails {
+                count: attributes.slider_count + 1,
+                sliders: attributes.slider_count
+            }]
+        );
+    }
+}
@@ -0,0 +1,435 @@
+//! Inverse performance calculation, e.g. the accuracy needed for a pp target
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{beatmap::Beatmap, pp_solver::PpSolver, ruleset::RulesetKind};
+//! # let beatmap = Beatmap::from_path("../../standard.osu")?;
+//! // Accuracy needed for 500pp with HDDT and a miss
+//! let solved = PpSolver::new(500.0)?
+//!     .mods(72)?
+//!     .misses(1)?
+//!     .required_accuracy(&beatmap, RulesetKind::Osu)?;
+//!
+//! println!("{:.2}% gives {:.2}pp", solved.value * 100.0, solved.performance.pp());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::Beatmap,
+    difficulty::any::{AnyDifficultyAttributes, AnyDifficultyCalculator},
+    error::{CalculationError, OsuError},
+    mods::{GameMods, GameModsError, IntoGameMods, native::ModCollection},
+    performance::{
+        ScoreStatistics,
+        any::{AnyPerformanceAttributes, AnyPerformanceCalculator},
+        simulate::{self, HitResultsTarget},
+    },
+    ruleset::{Ruleset, RulesetKind},
+};
+
+/// Steps in which accuracy is searched, i.e. 0.01%
+const ACCURACY_STEPS: i32 = 10_000;
+
+#[derive(Debug, ThisError)]
+pub enum PpSolverError {
+    #[error("Target pp must be positive, got {0}")]
+    InvalidTarget(f64),
+    #[error("Accuracy must be between 0 and 1, got {0}")]
+    InvalidAccuracy(f64),
+    #[error("Miss count can't be negative, got {0}")]
+    InvalidMisses(i32),
+    #[error("Combo can't be negative, got {0}")]
+    InvalidCombo(i32),
+    #[error("Target is unreachable even with an SS, which gives {ss_pp}pp")]
+    BeyondSs { ss_pp: f64 },
+    #[error("Target is unreachable with the fixed parameters, which give at most {best_pp}pp")]
+    Unreachable { best_pp: f64 },
+    #[error("Calculation failed")]
+    Calculation(#[from] CalculationError),
+}
+
+/// The value found by a [`PpSolver`] and the score it results in
+#[derive(Debug, PartialEq)]
+pub struct Solved<T> {
+    pub value: T,
+    /// The generated score, see [`simulate::any`]
+    pub score: ScoreStatistics,
+    pub performance: AnyPerformanceAttributes,
+}
+
+/// Searches the accuracy, miss count or combo needed for a pp target while
+/// keeping the other parameters fixed
+///
+/// Fixed parameters default to 100% accuracy, no misses and the beatmap's max
+/// combo. Performance is assumed to grow with accuracy and combo and to shrink
+/// with misses, so each search is a binary search over the possible values
+#[derive(Clone, Debug)]
+pub struct PpSolver {
+    target: f64,
+    mods: GameMods,
+    accuracy: f64,
+    misses: i32,
+    combo: Option<i32>,
+}
+
+impl PpSolver {
+    /// Creates a solver for `target` pp
+    ///
+    /// # Errors
+    /// Returns a [`PpSolverError::InvalidTarget`] if the target isn't positive
+    pub fn new(target: f64) -> Result<Self, PpSolverError> {
+        if !(target > 0.0 && target.is_finite()) {
+            return Err(PpSolverError::InvalidTarget(target));
+        }
+
+        Ok(Self {
+            target,
+            mods: GameMods::default(),
+            accuracy: 1.0,
+            misses: 0,
+            combo: None,
+        })
+    }
+
+    /// Sets the mods
+    ///
+    /// # Errors
+    /// Returns a [`GameModsError`] if the mods can't be converted
+    pub fn mods(mut self, mods: impl IntoGameMods) -> Result<Self, GameModsError> {
+        self.mods = mods.into_mods()?;
+
+        Ok(self)
+    }
+
+    /// Fixes the accuracy, between 0 and 1
+    ///
+    /// # Errors
+    /// Returns a [`PpSolverError::InvalidAccuracy`] if it's outside of 0..=1
+    pub fn accuracy(mut self, accuracy: f64) -> Result<Self, PpSolverError> {
+        if !(0.0..=1.0).contains(&accuracy) {
+            return Err(PpSolverError::InvalidAccuracy(accuracy));
+        }
+
+        self.accuracy = accuracy;
+
+        Ok(self)
+    }
+
+    /// Fixes the miss count
+    ///
+    /// # Errors
+    /// Returns a [`PpSolverError::InvalidMisses`] if it's negative
+    pub fn misses(mut self, misses: i32) -> Result<Self, PpSolverError> {
+        if misses < 0 {
+            return Err(PpSolverError::InvalidMisses(misses));
+        }
+
+        self.misses = misses;
+
+        Ok(self)
+    }
+
+    /// Fixes the combo, limited to the beatmap's max combo
+    ///
+    /// # Errors
+    /// Returns a [`PpSolverError::InvalidCombo`] if it's negative
+    pub fn combo(mut self, combo: i32) -> Result<Self, PpSolverError> {
+        if combo < 0 {
+            return Err(PpSolverError::InvalidCombo(combo));
+        }
+
+        self.combo = Some(combo);
+
+        Ok(self)
+    }
+
+    /// Lowest accuracy, in steps of 0.01%, that reaches the target
+    ///
+    /// # Errors
+    /// Returns a [`PpSolverError::BeyondSs`] or [`PpSolverError::Unreachable`] if
+    /// even 100% doesn't reach the target
+    /// Returns a [`PpSolverError`] if a calculation fails
+    pub fn required_accuracy(
+        &self,
+        beatmap: &Beatmap,
+        ruleset: RulesetKind,
+    ) -> Result<Solved<f64>, PpSolverError> {
+        let context = Context::new(self, beatmap, ruleset)?;
+        let target = |step: i32| HitResultsTarget {
+            combo: self.combo,
+            ..HitResultsTarget::new(f64::from(step) / f64::from(ACCURACY_STEPS), self.misses)
+        };
+
+        let step = context.solve(0, ACCURACY_STEPS, true, target)?;
+
+        Ok(context.solved(f64::from(step) / f64::from(ACCURACY_STEPS), &target(step))?)
+    }
+
+    /// Most misses that still reach the target
+    ///
+    /// # Errors
+    /// Returns a [`PpSolverError::BeyondSs`] or [`PpSolverError::Unreachable`] if
+    /// even no misses don't reach the target
+    /// Returns a [`PpSolverError`] if a calculation fails
+    pub fn allowed_misses(
+        &self,
+        beatmap: &Beatmap,
+        ruleset: RulesetKind,
+    ) -> Result<Solved<i32>, PpSolverError> {
+        let context = Context::new(self, beatmap, ruleset)?;
+        let target = |misses: i32| HitResultsTarget {
+            combo: self.combo,
+            ..HitResultsTarget::new(self.accuracy, misses)
+        };
+
+        let misses = context.solve(0, context.hit_results, false, target)?;
+
+        Ok(context.solved(misses, &target(misses))?)
+    }
+
+    /// Lowest combo that reaches the target
+    ///
+    /// # Errors
+    /// Returns a [`PpSolverError::BeyondSs`] or [`PpSolverError::Unreachable`] if
+    /// even the beatmap's max combo doesn't reach the target
+    /// Returns a [`PpSolverError`] if a calculation fails
+    pub fn required_combo(
+        &self,
+        beatmap: &Beatmap,
+        ruleset: RulesetKind,
+    ) -> Result<Solved<i32>, PpSolverError> {
+        let context = Context::new(self, beatmap, ruleset)?;
+        let target = |combo: i32| HitResultsTarget {
+            combo: Some(combo),
+            ..HitResultsTarget::new(self.accuracy, self.misses)
+        };
+
+        let max_combo = context.difficulty.max_combo();
+        let combo = context.solve(0, max_combo, true, target)?;
+
+        Ok(context.solved(combo, &target(combo))?)
+    }
+}
+
+/// Everything that's shared by the calculations of a single search
+struct Context<'a> {
+    target: f64,
+    beatmap: &'a Beatmap,
+    ruleset: Ruleset,
+    difficulty: AnyDifficultyAttributes,
+    performance: AnyPerformanceCalculator,
+    mods: ModCollection,
+    /// Hit results of an SS, i.e. the most possible misses
+    hit_results: i32,
+}
+
+impl<'a> Context<'a> {
+    fn new(
+        solver: &PpSolver,
+        beatmap: &'a Beatmap,
+        ruleset: RulesetKind,
+    ) -> Result<Self, CalculationError> {
+        let ruleset = Ruleset::new(ruleset).map_err(OsuError::from)?;
+        let difficulty = AnyDifficultyCalculator::from_kind(ruleset.kind, beatmap)?
+            .calculate_with_mods(&solver.mods)?;
+
+        let ss = simulate::any(beatmap, &difficulty, &HitResultsTarget::new(1.0, 0))?;
+        let hit_results =
+            ss.count_perfect + ss.count_great + ss.count_good + ss.count_ok + ss.count_meh;
+
+        Ok(Self {
+            target: solver.target,
+            beatmap,
+            performance: AnyPerformanceCalculator::new(ruleset.kind).map_err(OsuError::from)?,
+            mods: ModCollection::new()
+                .map_err(OsuError::from)?
+                .with_game_mods(&solver.mods)
+                .map_err(OsuError::from)?,
+            ruleset,
+            difficulty,
+            hit_results,
+        })
+    }
+
+    fn calculate(
+        &self,
+        target: &HitResultsTarget,
+    ) -> Result<(ScoreStatistics, AnyPerformanceAttributes), CalculationError> {
+        let score = simulate::any(self.beatmap, &self.difficulty, target)?;
+        let performance = self.performance.calculate_with_collection(
+            &self.ruleset,
+            &score,
+            self.beatmap,
+            &self.mods,
+            &self.difficulty,
+        )?;
+
+        Ok((score, performance))
+    }
+
+    fn reaches(&self, target: &HitResultsTarget) -> Result<bool, CalculationError> {
+        Ok(self.calculate(target)?.1.pp() >= self.target)
+    }
+
+    /// Binary search over `min..=max` for the value closest to the other end
+    /// that still reaches the target
+    ///
+    /// If `increasing`, higher values give more pp and the lowest reaching value
+    /// is returned, otherwise the highest
+    fn solve(
+        &self,
+        min: i32,
+        max: i32,
+        increasing: bool,
+        target: impl Fn(i32) -> HitResultsTarget,
+    ) -> Result<i32, PpSolverError> {
+        let best = if increasing { max } else { min };
+        let best_pp = self.calculate(&target(best))?.1.pp();
+
+        if best_pp < self.target {
+            let (_, ss) = self.calculate(&HitResultsTarget::new(1.0, 0))?;
+
+            return Err(if ss.pp() < self.target {
+                PpSolverError::BeyondSs { ss_pp: ss.pp() }
+            } else {
+                PpSolverError::Unreachable { best_pp }
+            });
+        }
+
+        // Invariant: `best` reaches the target
+        let (mut reaching, mut failing) = if increasing {
+            (max, min - 1)
+        } else {
+            (min, max + 1)
+        };
+
+        while (reaching - failing).abs() > 1 {
+            let mid = failing + (reaching - failing) / 2;
+
+            if self.reaches(&target(mid))? {
+                reaching = mid;
+            } else {
+                failing = mid;
+            }
+        }
+
+        Ok(reaching)
+    }
+
+    fn solved<T>(
+        &self,
+        value: T,
+        target: &HitResultsTarget,
+    ) -> Result<Solved<T>, CalculationError> {
+        let (score, performance) = self.calculate(target)?;
+
+        Ok(Solved {
+            value,
+            score,
+            performance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PpSolver, PpSolverError};
+    use crate::{beatmap::Beatmap, ruleset::RulesetKind, utils::initialize_path};
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert!(matches!(
+            PpSolver::new(-5.0),
+            Err(PpSolverError::InvalidTarget(_))
+        ));
+        assert!(matches!(
+            PpSolver::new(f64::NAN),
+            Err(PpSolverError::InvalidTarget(_))
+        ));
+
+        let solver = PpSolver::new(100.0).unwrap();
+        assert!(matches!(
+            solver.clone().accuracy(1.5),
+            Err(PpSolverError::InvalidAccuracy(_))
+        ));
+        assert!(matches!(
+            solver.clone().misses(-1),
+            Err(PpSolverError::InvalidMisses(-1))
+        ));
+        assert!(matches!(
+            solver.combo(-1),
+            Err(PpSolverError::InvalidCombo(-1))
+        ));
+    }
+
+    #[test]
+    fn test_required_accuracy() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let ss = PpSolver::new(1.0)
+            .unwrap()
+            .required_accuracy(&beatmap, RulesetKind::Osu)
+            .unwrap();
+        let target = ss.performance.pp() * 0.9;
+
+        let solved = PpSolver::new(target)
+            .unwrap()
+            .required_accuracy(&beatmap, RulesetKind::Osu)
+            .unwrap();
+
+        assert!(solved.value < 1.0);
+        assert!(solved.performance.pp() >= target);
+
+        let below = PpSolver::new(target)
+            .unwrap()
+            .accuracy(((solved.value * 10_000.0).round() - 1.0) / 10_000.0)
+            .unwrap()
+            .allowed_misses(&beatmap, RulesetKind::Osu);
+        assert!(matches!(below, Err(PpSolverError::Unreachable { .. })));
+    }
+
+    #[test]
+    fn test_required_accuracy_catch() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let Err(PpSolverError::BeyondSs { ss_pp }) = PpSolver::new(100_000.0)
+            .unwrap()
+            .required_accuracy(&beatmap, RulesetKind::Catch)
+        else {
+            panic!("expected the target to be beyond an SS");
+        };
+
+        let solved = PpSolver::new(ss_pp * 0.9)
+            .unwrap()
+            .required_accuracy(&beatmap, RulesetKind::Catch)
+            .unwrap();
+
+        assert!(solved.value > 0.0 && solved.value < 1.0);
+    }
+
+    #[test]
+    fn test_allowed_misses_and_combo() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let solver = PpSolver::new(50.0).unwrap();
+
+        let misses = solver.allowed_misses(&beatmap, RulesetKind::Osu).unwrap();
+        assert_eq!(misses.score.count_miss, misses.value);
+        assert!(misses.performance.pp() >= 50.0);
+
+        let combo = solver.required_combo(&beatmap, RulesetKind::Osu).unwrap();
+        assert_eq!(combo.score.max_combo, combo.value);
+        assert!(combo.performance.pp() >= 50.0);
+    }
+
+    #[test]
+    fn test_beyond_ss() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+
+        assert!(matches!(
+            PpSolver::new(100_000.0)
+                .unwrap()
+                .required_accuracy(&beatmap, RulesetKind::Osu),
+            Err(PpSolverError::BeyondSs { .. })
+        ));
+    }
+}
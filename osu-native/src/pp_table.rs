@@ -0,0 +1,229 @@
+//! Performance at several accuracies, e.g. for the pp column of a beatmap page
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{beatmap::Beatmap, pp_table::PpTable, ruleset::RulesetKind};
+//! # let beatmap = Beatmap::from_path("../../standard.osu")?;
+//! // HDDT, 95% to 100% with and without a miss
+//! let rows = PpTable::default()
+//!     .misses([0, 1])?
+//!     .mods(72)?
+//!     .calculate(&beatmap, RulesetKind::Osu)?;
+//!
+//! for row in rows {
+//!     println!("{:.2}% {}x: {:.2}pp", row.accuracy * 100.0, row.misses, row.performance.pp());
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::Beatmap,
+    difficulty::any::AnyDifficultyCalculator,
+    error::{CalculationError, OsuError},
+    mods::{GameMods, GameModsError, IntoGameMods, native::ModCollection},
+    performance::{
+        ScoreStatistics,
+        any::{AnyPerformanceAttributes, AnyPerformanceCalculator},
+        simulate::{self, HitResultsTarget},
+    },
+    ruleset::{Ruleset, RulesetKind},
+};
+
+/// Accuracies of a [`PpTable::default`]
+pub const DEFAULT_ACCURACIES: [f64; 5] = [0.95, 0.97, 0.98, 0.99, 1.0];
+
+#[derive(Debug, ThisError)]
+pub enum PpTableError {
+    #[error("Accuracy must be between 0 and 1, got {0}")]
+    InvalidAccuracy(f64),
+    #[error("Miss count can't be negative, got {0}")]
+    InvalidMisses(i32),
+}
+
+/// A single row of a [`PpTable`]
+#[derive(Debug, PartialEq)]
+pub struct PpRow {
+    /// The requested accuracy, the score's accuracy may differ slightly
+    pub accuracy: f64,
+    pub misses: i32,
+    /// The score generated for the accuracy and misses, see [`simulate::any`]
+    pub score: ScoreStatistics,
+    pub performance: AnyPerformanceAttributes,
+}
+
+/// Calculates performance for every combination of accuracies and miss counts
+///
+/// Difficulty is calculated once and all rows share one performance calculator
+/// and one native mod collection
+#[derive(Clone, Debug)]
+pub struct PpTable {
+    accuracies: Vec<f64>,
+    misses: Vec<i32>,
+    mods: GameMods,
+}
+
+impl Default for PpTable {
+    /// [`DEFAULT_ACCURACIES`] without misses or mods
+    fn default() -> Self {
+        Self {
+            accuracies: DEFAULT_ACCURACIES.to_vec(),
+            misses: vec![0],
+            mods: GameMods::default(),
+        }
+    }
+}
+
+impl PpTable {
+    /// Creates a table for the given accuracies between 0 and 1, without misses
+    ///
+    /// # Errors
+    /// Returns a [`PpTableError::InvalidAccuracy`] for the first accuracy outside of 0..=1
+    pub fn new(accuracies: impl Into<Vec<f64>>) -> Result<Self, PpTableError> {
+        let accuracies = accuracies.into();
+
+        if let Some(accuracy) = accuracies
+            .iter()
+            .find(|accuracy| !(0.0..=1.0).contains(*accuracy))
+        {
+            return Err(PpTableError::InvalidAccuracy(*accuracy));
+        }
+
+        Ok(Self {
+            accuracies,
+            ..Self::default()
+        })
+    }
+
+    /// Sets the miss counts, each one gets a row per accuracy
+    ///
+    /// # Errors
+    /// Returns a [`PpTableError::InvalidMisses`] for the first negative count
+    pub fn misses(mut self, misses: impl Into<Vec<i32>>) -> Result<Self, PpTableError> {
+        let misses = misses.into();
+
+        if let Some(count) = misses.iter().find(|count| **count < 0) {
+            return Err(PpTableError::InvalidMisses(*count));
+        }
+
+        self.misses = misses;
+
+        Ok(self)
+    }
+
+    /// Sets the mods applied to every row
+    ///
+    /// # Errors
+    /// Returns a [`GameModsError`] if the mods can't be converted
+    pub fn mods(mut self, mods: impl IntoGameMods) -> Result<Self, GameModsError> {
+        self.mods = mods.into_mods()?;
+
+        Ok(self)
+    }
+
+    /// Calculates the rows for `beatmap` played in `ruleset`, grouped by miss
+    /// count and in the given order
+    ///
+    /// # Errors
+    /// Returns a [`CalculationError`] if the difficulty or any performance
+    /// calculation fails, or if the scores can't be generated, see [`simulate::any`]
+    pub fn calculate(
+        &self,
+        beatmap: &Beatmap,
+        ruleset: RulesetKind,
+    ) -> Result<Vec<PpRow>, CalculationError> {
+        let ruleset = Ruleset::new(ruleset).map_err(OsuError::from)?;
+        let difficulty = AnyDifficultyCalculator::from_kind(ruleset.kind, beatmap)?
+            .calculate_with_mods(&self.mods)?;
+
+        let performance = AnyPerformanceCalculator::new(ruleset.kind).map_err(OsuError::from)?;
+        let mods = ModCollection::new()
+            .map_err(OsuError::from)?
+            .with_game_mods(&self.mods)
+            .map_err(OsuError::from)?;
+
+        let mut rows = Vec::with_capacity(self.misses.len() * self.accuracies.len());
+
+        for &misses in &self.misses {
+            for &accuracy in &self.accuracies {
+                let score = simulate::any(
+                    beatmap,
+                    &difficulty,
+                    &HitResultsTarget::new(accuracy, misses),
+                )?;
+                let performance = performance.calculate_with_collection(
+                    &ruleset,
+                    &score,
+                    beatmap,
+                    &mods,
+                    &difficulty,
+                )?;
+
+                rows.push(PpRow {
+                    accuracy,
+                    misses,
+                    score,
+                    performance,
+                });
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_ACCURACIES, PpTable, PpTableError};
+    use crate::{beatmap::Beatmap, ruleset::RulesetKind, utils::initialize_path};
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            PpTable::new([0.98, 98.0]),
+            Err(PpTableError::InvalidAccuracy(98.0))
+        ));
+        assert!(matches!(
+            PpTable::default().misses([0, -1]),
+            Err(PpTableError::InvalidMisses(-1))
+        ));
+    }
+
+    #[test]
+    fn test_pp_table() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let rows = PpTable::default()
+            .misses([0, 5])
+            .unwrap()
+            .calculate(&beatmap, RulesetKind::Osu)
+            .unwrap();
+
+        assert_eq!(rows.len(), 2 * DEFAULT_ACCURACIES.len());
+
+        let (no_misses, misses) = rows.split_at(DEFAULT_ACCURACIES.len());
+
+        for pair in no_misses.windows(2) {
+            assert!(pair[0].performance.pp() < pair[1].performance.pp());
+        }
+
+        for (row, missed) in no_misses.iter().zip(misses) {
+            assert_eq!(row.accuracy, missed.accuracy);
+            assert_eq!(missed.score.count_miss, 5);
+            assert!(missed.performance.pp() < row.performance.pp());
+        }
+    }
+
+    #[test]
+    fn test_pp_table_catch() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let rows = PpTable::default()
+            .calculate(&beatmap, RulesetKind::Catch)
+            .unwrap();
+
+        for pair in rows.windows(2) {
+            assert!(pair[0].score.accuracy < pair[1].score.accuracy);
+            assert!(pair[0].performance.pp() < pair[1].performance.pp());
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Difficulty and performance across a range of playback rates
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::{beatmap::Beatmap, rate::RateCurve, ruleset::RulesetKind};
+//! # let beatmap = Beatmap::from_path("../../standard.osu")?;
+//! // HD, 0.5x to 2.0x in 0.05 steps
+//! let samples = RateCurve::new(0.5, 2.0, 0.05)?
+//!     .mods(8)?
+//!     .with_pp(true)
+//!     .calculate(&beatmap, RulesetKind::Osu)?;
+//!
+//! for sample in samples {
+//!     println!("{:.2}x: {:.2}*", sample.rate, sample.difficulty.star_rating());
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use rosu_mods::{GameModSimple, simple::SettingSimple};
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::Beatmap,
+    difficulty::any::{AnyDifficultyAttributes, AnyDifficultyCalculator},
+    error::{CalculationError, OsuError},
+    mods::{GameMods, GameModsError, IntoGameMods},
+    performance::{
+        any::AnyPerformanceCalculator,
+        simulate::{self, HitResultsTarget},
+    },
+    ruleset::{Ruleset, RulesetKind},
+};
+
+/// Slowest rate supported by HT/DC
+pub const MIN_RATE: f64 = 0.5;
+/// Fastest rate supported by DT/NC
+pub const MAX_RATE: f64 = 2.0;
+
+#[derive(Debug, ThisError)]
+pub enum RateCurveError {
+    #[error("Invalid rate range {min}..={max} with step {step}")]
+    InvalidRange { min: f64, max: f64, step: f64 },
+    #[error("{0} can't be combined with a fixed rate")]
+    IncompatibleMod(String),
+    #[error("Game mods error")]
+    Mods(#[from] GameModsError),
+}
+
+/// A single point of a [`RateCurve`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateSample {
+    pub rate: f64,
+    /// The mods the sample was calculated with, including the rate mod
+    pub mods: GameMods,
+    pub difficulty: AnyDifficultyAttributes,
+    /// Performance of a perfect score, if requested
+    pub ss_pp: Option<f64>,
+}
+
+/// Samples difficulty, and optionally SS performance, at evenly spaced rates
+///
+/// Rates above 1 are applied through DT, rates below 1 through HT, using their
+/// `speed_change` setting. Rate mods in the base mods are replaced
+#[derive(Clone, Debug)]
+pub struct RateCurve {
+    min: f64,
+    max: f64,
+    step: f64,
+    mods: GameMods,
+    with_pp: bool,
+}
+
+impl RateCurve {
+    /// Creates a curve from `min` to `max`, both inclusive
+    ///
+    /// # Errors
+    /// Returns a [`RateCurveError::InvalidRange`] if the range is empty, the step
+    /// isn't positive, or the range exceeds [`MIN_RATE`]..=[`MAX_RATE`]
+    pub fn new(min: f64, max: f64, step: f64) -> Result<Self, RateCurveError> {
+        let valid = MIN_RATE <= min && min <= max && max <= MAX_RATE && step > 0.0;
+
+        if !valid {
+            return Err(RateCurveError::InvalidRange { min, max, step });
+        }
+
+        Ok(Self {
+            min,
+            max,
+            step,
+            mods: GameMods::default(),
+            with_pp: false,
+        })
+    }
+
+    /// Sets the mods applied at every rate
+    ///
+    /// # Errors
+    /// Returns a [`RateCurveError::IncompatibleMod`] if `mods` contain a mod that
+    /// changes the rate over time, like WU, WD or AS
+    pub fn mods(mut self, mods: impl IntoGameMods) -> Result<Self, RateCurveError> {
+        let mods = mods.into_mods()?;
+
+        if let Some(gamemod) = mods
+            .0
+            .iter()
+            .find(|gamemod| matches!(gamemod.acronym.as_str(), "WU" | "WD" | "AS"))
+        {
+            return Err(RateCurveError::IncompatibleMod(
+                gamemod.acronym.as_str().to_owned(),
+            ));
+        }
+
+        self.mods = mods;
+
+        Ok(self)
+    }
+
+    /// Whether to also calculate the performance of an SS at every rate
+    pub fn with_pp(mut self, with_pp: bool) -> Self {
+        self.with_pp = with_pp;
+        self
+    }
+
+    /// The sampled rates, rounded to avoid accumulating floating point error
+    pub fn rates(&self) -> Vec<f64> {
+        let count = ((self.max - self.min) / self.step + 1e-9).floor() as usize;
+
+        (0..=count)
+            .map(|i| ((self.min + i as f64 * self.step) * 1e6).round() / 1e6)
+            .collect()
+    }
+
+    /// Calculates a sample per rate for `beatmap` played in `ruleset`
+    ///
+    /// The ruleset and calculators are created once and shared by all samples
+    ///
+    /// # Errors
+    /// Returns a [`CalculationError`] if any calculation fails, or if an SS
+    /// can't be generated for the performance, see [`simulate::any`]
+    pub fn calculate(
+        &self,
+        beatmap: &Beatmap,
+        ruleset: RulesetKind,
+    ) -> Result<Vec<RateSample>, CalculationError> {
+        let rates = self.rates();
+        let mods: Vec<_> = rates
+            .iter()
+            .map(|rate| with_rate(&self.mods, *rate))
+            .collect();
+
+        let ruleset = Ruleset::new(ruleset).map_err(OsuError::from)?;
+        let difficulty = AnyDifficultyCalculator::from_kind(ruleset.kind, beatmap)?;
+        let attributes = difficulty.calculate_batch(&mods)?;
+
+        let performance = if self.with_pp {
+            Some(AnyPerformanceCalculator::new(ruleset.kind).map_err(OsuError::from)?)
+        } else {
+            None
+        };
+
+        rates
+            .into_iter()
+            .zip(mods)
+            .zip(attributes)
+            .map(|((rate, mods), difficulty)| {
+                let ss_pp = match performance {
+                    Some(ref performance) => {
+                        let score = simulate::any(beatmap, &difficulty, &perfect(&difficulty))?;
+                        let attributes =
+                            performance.calculate(&ruleset, &score, beatmap, &mods, &difficulty)?;
+
+                        Some(attributes.pp())
+                    }
+                    None => None,
+                };
+
+                Ok(RateSample {
+                    rate,
+                    mods,
+                    difficulty,
+                    ss_pp,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Replaces the rate mods of `mods` with DT or HT at `rate`
+fn with_rate(mods: &GameMods, rate: f64) -> GameMods {
+    let mut mods: Vec<_> = mods
+        .0
+        .iter()
+        .filter(|gamemod| !matches!(gamemod.acronym.as_str(), "DT" | "NC" | "HT" | "DC"))
+        .cloned()
+        .collect();
+
+    let acronym = if rate > 1.0 {
+        Some("DT")
+    } else if rate < 1.0 {
+        Some("HT")
+    } else {
+        None
+    };
+
+    if let Some(acronym) = acronym.and_then(|acronym| acronym.parse().ok()) {
+        mods.push(GameModSimple {
+            acronym,
+            settings: [("speed_change".into(), SettingSimple::Number(rate))]
+                .into_iter()
+                .collect(),
+        });
+    }
+
+    GameMods(mods)
+}
+
+/// Target of an SS, only 320s for mania since they're worth more pp than 300s
+fn perfect(difficulty: &AnyDifficultyAttributes) -> HitResultsTarget {
+    let target = HitResultsTarget::new(1.0, 0);
+
+    match difficulty {
+        AnyDifficultyAttributes::Mania(_) => HitResultsTarget {
+            count_great: Some(0),
+            ..target
+        },
+        _ => target,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rosu_mods::{Acronym, GameModSimple};
+
+    use super::{RateCurve, RateCurveError, with_rate};
+    use crate::{
+        beatmap::Beatmap,
+        mods::{GameMods, IntoGameMods},
+        ruleset::RulesetKind,
+        utils::initialize_path,
+    };
+
+    fn simple(acronym: &str) -> GameModSimple {
+        GameModSimple {
+            acronym: Acronym::from_str(acronym).unwrap(),
+            settings: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_rates() {
+        let rates = RateCurve::new(0.5, 2.0, 0.05).unwrap().rates();
+
+        assert_eq!(rates.len(), 31);
+        assert_eq!(rates[0], 0.5);
+        assert_eq!(rates[10], 1.0);
+        assert_eq!(rates[30], 2.0);
+
+        assert_eq!(RateCurve::new(1.0, 1.0, 0.1).unwrap().rates(), [1.0]);
+    }
+
+    #[test]
+    fn test_invalid_curves() {
+        for (min, max, step) in [
+            (1.5, 1.0, 0.1),
+            (0.25, 1.0, 0.1),
+            (1.0, 2.5, 0.1),
+            (1.0, 1.5, 0.0),
+        ] {
+            assert!(matches!(
+                RateCurve::new(min, max, step),
+                Err(RateCurveError::InvalidRange { .. })
+            ));
+        }
+
+        let curve = RateCurve::new(1.0, 1.5, 0.1).unwrap();
+        assert!(matches!(
+            curve.mods(vec![simple("HD"), simple("WU")]),
+            Err(RateCurveError::IncompatibleMod(acronym)) if acronym == "WU"
+        ));
+    }
+
+    #[test]
+    fn test_rate_replaces_rate_mods() {
+        let base = vec![simple("HD"), simple("NC")].into_mods().unwrap();
+
+        let faster = with_rate(&base, 1.3);
+        assert!(faster.contains("HD") && faster.contains("DT") && !faster.contains("NC"));
+        assert_eq!(faster.clock_rate(), 1.3);
+
+        let slower = with_rate(&base, 0.8);
+        assert!(slower.contains("HT") && !slower.contains("DT"));
+        assert_eq!(slower.clock_rate(), 0.8);
+
+        let normal = with_rate(&base, 1.0);
+        assert_eq!(normal.len(), 1);
+        assert_eq!(normal.clock_rate(), 1.0);
+
+        assert!(with_rate(&GameMods::default(), 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_rate_curve() {
+        let beatmap = Beatmap::from_path(initialize_path()).unwrap();
+        let samples = RateCurve::new(0.75, 1.5, 0.25)
+            .unwrap()
+            .with_pp(true)
+            .calculate(&beatmap, RulesetKind::Osu)
+            .unwrap();
+
+        assert_eq!(samples.len(), 4);
+
+        for pair in samples.windows(2) {
+            assert!(pair[1].difficulty.star_rating() > pair[0].difficulty.star_rating());
+            assert!(pair[1].ss_pp > pair[0].ss_pp);
+        }
+    }
+}
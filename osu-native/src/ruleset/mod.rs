@@ -11,8 +11,14 @@ use crate::{
     utils::{HasNative, NativeType, StringError, read_native_string},
 };
 
+/// With the `serde` feature, serialized as lazer's numeric ruleset ID
 #[non_exhaustive]
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "i32", try_from = "i32")
+)]
 pub enum RulesetKind {
     #[default]
     Osu = 0,
@@ -50,6 +56,14 @@ impl TryFrom<i32> for RulesetKind {
     }
 }
 
+/// A ruleset-specific value was used with a different ruleset
+#[derive(Debug, ThisError)]
+#[error("Expected {expected:?} ruleset, got {actual:?}")]
+pub struct RulesetMismatch {
+    pub expected: RulesetKind,
+    pub actual: RulesetKind,
+}
+
 #[derive(Debug, ThisError)]
 pub enum RulesetError {
     #[error(transparent)]
@@ -135,4 +149,15 @@ mod tests {
     generate_ruleset_tests!(Taiko, "taiko");
     generate_ruleset_tests!(Mania, "mania");
     generate_ruleset_tests!(Catch, "fruits");
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ruleset_kind_serde() {
+        assert_eq!(serde_json::to_string(&RulesetKind::Mania).unwrap(), "3");
+        assert_eq!(
+            serde_json::from_str::<RulesetKind>("1").unwrap(),
+            RulesetKind::Taiko
+        );
+        assert!(serde_json::from_str::<RulesetKind>("4").is_err());
+    }
 }
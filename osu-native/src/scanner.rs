@@ -0,0 +1,324 @@
+//! Parallel difficulty calculation over a songs folder
+//!
+//! # Examples
+//! ```no_run
+//! # use osu_native::scanner::Scanner;
+//! // NM, HR and DT
+//! let results = Scanner::new("Songs").mods([0, 16, 64])?.threads(8).scan()?;
+//!
+//! for entry in results {
+//!     match entry.result {
+//!         Ok(scanned) => println!("{}: {} mod combinations", scanned.version, scanned.attributes.len()),
+//!         Err(err) => eprintln!("{}: {err}", entry.path.display()),
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+    },
+    thread::{self, JoinHandle},
+};
+
+use thiserror::Error as ThisError;
+
+use crate::{
+    beatmap::{Beatmap, BeatmapError, checksum::visit_osu_files},
+    difficulty::any::{AnyDifficultyAttributes, AnyDifficultyCalculator},
+    error::OsuError,
+    mods::{GameMods, GameModsError, IntoGameMods},
+    ruleset::{InvalidRulesetId, RulesetKind},
+    utils::StringError,
+};
+
+#[derive(Debug, ThisError)]
+pub enum ScanError {
+    #[error("Failed to load beatmap")]
+    Beatmap(#[from] BeatmapError),
+    #[error("Failed to read beatmap metadata")]
+    Metadata(#[from] StringError),
+    #[error(transparent)]
+    InvalidRuleset(#[from] InvalidRulesetId),
+    #[error("Game mods error")]
+    Mods(#[from] GameModsError),
+    #[error("Difficulty calculation failed")]
+    Calculation(#[from] OsuError),
+}
+
+/// A successfully processed beatmap
+#[derive(Debug)]
+pub struct ScannedBeatmap {
+    pub checksum: String,
+    pub title: String,
+    pub artist: String,
+    pub version: String,
+    pub ruleset: RulesetKind,
+    /// One entry per configured mod combination, in configuration order
+    pub attributes: Vec<(GameMods, AnyDifficultyAttributes)>,
+}
+
+/// Outcome of processing a single .osu file
+#[derive(Debug)]
+pub struct ScanEntry {
+    pub path: PathBuf,
+    pub result: Result<ScannedBeatmap, ScanError>,
+}
+
+/// Walks a directory tree and calculates difficulty for every .osu file in it
+///
+/// Files are processed in sorted path order. To resume an interrupted scan,
+/// pass the paths that were already stored to [`Scanner::skip`]
+#[derive(Clone, Debug)]
+pub struct Scanner {
+    root: PathBuf,
+    mods: Vec<GameMods>,
+    threads: usize,
+    skip: HashSet<PathBuf>,
+}
+
+impl Scanner {
+    /// Creates a scanner for `root` that calculates nomod attributes on one
+    /// thread per available core
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            mods: vec![GameMods::default()],
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            skip: HashSet::new(),
+        }
+    }
+
+    /// Sets the mod combinations to calculate for every beatmap
+    ///
+    /// # Errors
+    /// Returns a [`GameModsError`] if any of the combinations fails to convert
+    pub fn mods<M: IntoGameMods>(
+        mut self,
+        combinations: impl IntoIterator<Item = M>,
+    ) -> Result<Self, GameModsError> {
+        self.mods = combinations
+            .into_iter()
+            .map(IntoGameMods::into_mods)
+            .collect::<Result<_, _>>()?;
+
+        Ok(self)
+    }
+
+    /// Sets the number of worker threads, at least one is always used
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Excludes already processed files from the scan
+    pub fn skip(mut self, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.skip.extend(paths);
+        self
+    }
+
+    /// Starts the scan, returning an iterator over results as they complete
+    ///
+    /// Results arrive in completion order, not path order
+    ///
+    /// # Errors
+    /// Returns an [`io::Error`] if the directory tree can't be walked.
+    /// Errors for individual files are reported through [`ScanEntry::result`]
+    pub fn scan(self) -> io::Result<ScanResults> {
+        let mut paths = Vec::new();
+
+        visit_osu_files(&self.root, &mut |path| {
+            if !self.skip.contains(&path) {
+                paths.push(path);
+            }
+
+            Ok(true)
+        })?;
+
+        paths.sort();
+
+        let total = paths.len();
+        let paths = Arc::new(paths);
+        let mods = Arc::new(self.mods);
+        let next = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::sync_channel(self.threads * 2);
+
+        let workers = (0..self.threads.min(total))
+            .map(|_| {
+                let paths = Arc::clone(&paths);
+                let mods = Arc::clone(&mods);
+                let next = Arc::clone(&next);
+                let sender = sender.clone();
+
+                thread::spawn(move || work(&paths, &mods, &next, &sender))
+            })
+            .collect();
+
+        Ok(ScanResults {
+            receiver,
+            workers,
+            total,
+        })
+    }
+}
+
+/// Streaming results of [`Scanner::scan`]
+///
+/// Dropping it early stops the workers after their current file
+pub struct ScanResults {
+    receiver: Receiver<ScanEntry>,
+    workers: Vec<JoinHandle<()>>,
+    total: usize,
+}
+
+impl ScanResults {
+    /// Number of files that will be processed
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+impl Iterator for ScanResults {
+    type Item = ScanEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.receiver.recv().ok();
+
+        if entry.is_none() {
+            for worker in self.workers.drain(..) {
+                let _ = worker.join();
+            }
+        }
+
+        entry
+    }
+}
+
+fn work(paths: &[PathBuf], mods: &[GameMods], next: &AtomicUsize, sender: &SyncSender<ScanEntry>) {
+    loop {
+        let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) else {
+            return;
+        };
+
+        let entry = ScanEntry {
+            path: path.clone(),
+            result: scan_file(path, mods),
+        };
+
+        if sender.send(entry).is_err() {
+            // Receiver is gone, nobody is interested in further results
+            return;
+        }
+    }
+}
+
+fn scan_file(path: &Path, mods: &[GameMods]) -> Result<ScannedBeatmap, ScanError> {
+    let beatmap = Beatmap::from_path(path)?;
+    let ruleset = beatmap.ruleset()?;
+
+    let attributes = AnyDifficultyCalculator::from_kind(ruleset, &beatmap)?
+        .calculate_batch(mods)?
+        .into_iter()
+        .zip(mods.iter().cloned())
+        .map(|(attributes, mods)| (mods, attributes))
+        .collect();
+
+    Ok(ScannedBeatmap {
+        checksum: beatmap.checksum().to_owned(),
+        title: beatmap.title()?,
+        artist: beatmap.artist()?,
+        version: beatmap.version()?,
+        ruleset,
+        attributes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::{ScanError, Scanner};
+    use crate::{
+        ruleset::RulesetKind,
+        utils::{initialize_path, unique_temp_dir},
+    };
+
+    fn songs_folder(name: &str) -> PathBuf {
+        let dir = unique_temp_dir(name);
+        let set = dir.join("2305059 John Grant - Toy Box");
+        fs::create_dir_all(&set).unwrap();
+        fs::copy(initialize_path(), set.join("expert.osu")).unwrap();
+        // Without the format header lazer has no decoder for the file
+        fs::write(set.join("broken.osu"), "[HitObjects]\n256,192,0,1,0").unwrap();
+        fs::write(set.join("audio.mp3"), "ID3").unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn test_scan_songs_folder() {
+        let dir = songs_folder("osu-native-scan");
+        let results = Scanner::new(&dir)
+            .mods([0, 64])
+            .unwrap()
+            .threads(2)
+            .scan()
+            .unwrap();
+        assert_eq!(results.total(), 2);
+
+        let mut entries: Vec<_> = results.collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(entries.len(), 2);
+
+        assert!(matches!(entries[0].result, Err(ScanError::Beatmap(_))));
+
+        let scanned = entries[1].result.as_ref().unwrap();
+        assert_eq!(scanned.version, "Expert");
+        assert_eq!(scanned.checksum, "f52cf288046c2f07335a280096a5b4fb");
+        assert_eq!(scanned.attributes.len(), 2);
+        assert!(
+            scanned
+                .attributes
+                .iter()
+                .all(|(_, attributes)| attributes.ruleset() == RulesetKind::Osu)
+        );
+
+        let star_ratings: Vec<_> = scanned
+            .attributes
+            .iter()
+            .map(|(_, attributes)| attributes.star_rating())
+            .collect();
+        assert!(star_ratings[1] > star_ratings[0]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_scan_skips_processed_files() {
+        let dir = songs_folder("osu-native-scan-resume");
+        let set = dir.join("2305059 John Grant - Toy Box");
+
+        let results = Scanner::new(&dir)
+            .skip([set.join("expert.osu"), set.join("broken.osu")])
+            .scan()
+            .unwrap();
+
+        assert_eq!(results.total(), 0);
+        assert_eq!(results.count(), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_scan_missing_folder() {
+        let dir = unique_temp_dir("osu-native-scan-missing");
+
+        assert!(Scanner::new(dir).scan().is_err());
+    }
+}
@@ -73,6 +73,27 @@ pub fn initialize_path() -> std::path::PathBuf {
 
     path
 }
+/// Fresh directory path under the system's temp dir, unique across tests and runs
+#[cfg(test)]
+pub fn unique_temp_dir(name: &str) -> std::path::PathBuf {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+
+    std::env::temp_dir().join(format!(
+        "{name}-{}-{nanos}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
 #[cfg(doctest)]
 pub fn initialize_path() -> std::path::PathBuf {
     let manifest_path = std::env!("CARGO_MANIFEST_DIR");